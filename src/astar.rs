@@ -0,0 +1,136 @@
+use crate::bfs::Node;
+use crate::bfs::TracePath;
+use crate::greedy::Candidate;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Finds a shortest path from the start state to a goal state using the A* search algorithm.
+///
+/// - `start_state` is the initial state.
+/// - `is_goal` is a function that checks if a given state is the goal state.
+/// - `neighbors` is a function that returns the next states of a given state.
+/// - `key` is a function that returns the key identifying equivalent states.
+/// - `heuristic` is a function that estimates the number of moves from a given state to the goal.
+///   It must never overestimate, or the path found may not be the shortest.
+/// - `on_expand` is called with the depth of each state whose neighbors are expanded.
+///
+/// States reached again by a shorter path are expanded again, so the heuristic need not be consistent.
+///
+/// Returns an `Option<Vec<T>>` containing the path from the start state to the goal state if found, or `None` if no path exists.
+pub fn find_path<T, K, FGoal, FNext, FKey, FHeuristic, FExpand>(
+    start_state: &T,
+    is_goal: FGoal,
+    neighbors: FNext,
+    key: FKey,
+    heuristic: FHeuristic,
+    mut on_expand: FExpand,
+) -> Option<Vec<T>>
+where
+    T: Clone,
+    K: Eq + Hash,
+    FGoal: Fn(&T) -> bool,
+    FNext: Fn(&T) -> Vec<T>,
+    FKey: Fn(&T) -> K,
+    FHeuristic: Fn(&T) -> usize,
+    FExpand: FnMut(usize),
+{
+    let mut open_list = BinaryHeap::new();
+    let mut best_depths = HashMap::new();
+    let mut order = 0;
+
+    best_depths.insert(key(start_state), 0);
+    open_list.push(Candidate {
+        score: heuristic(start_state),
+        order,
+        node: Node::new(start_state.clone(), None),
+    });
+
+    while let Some(Candidate { score, node, .. }) = open_list.pop() {
+        let depth = score - heuristic(&node.state);
+        if best_depths
+            .get(&key(&node.state))
+            .is_some_and(|&best| best < depth)
+        {
+            // A shorter path to this state was already found.
+            continue;
+        }
+        if is_goal(&node.state) {
+            // Found the goal state.
+            return Some(node.trace_path());
+        }
+        on_expand(depth);
+        for next_state in (neighbors)(&node.state) {
+            let next_depth = depth + 1;
+            let next_key = key(&next_state);
+            if best_depths
+                .get(&next_key)
+                .is_some_and(|&best| best <= next_depth)
+            {
+                // Not shorter than the known path.
+                continue;
+            }
+            best_depths.insert(next_key, next_depth);
+            order += 1;
+            open_list.push(Candidate {
+                score: next_depth + heuristic(&next_state),
+                order,
+                node: Node::new(next_state, Some(node.clone())),
+            });
+        }
+    }
+    None // Not Found.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_path_with_heuristic() {
+        // Arrange: A grid of 5x5 cells, moving from (0, 0) to (4, 0)
+        let goal = (4, 0);
+        let is_goal = |&p: &(i32, i32)| p == goal;
+        let neighbors = |&(x, y): &(i32, i32)| {
+            [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+                .into_iter()
+                .filter(|&(x, y)| (0..5).contains(&x) && (0..5).contains(&y))
+                .collect()
+        };
+        let key = |&p: &(i32, i32)| p;
+        let manhattan = |&(x, y): &(i32, i32)| ((goal.0 - x).abs() + (goal.1 - y).abs()) as usize;
+        let mut expanded_with_heuristic = 0;
+        let mut expanded_without_heuristic = 0;
+
+        // Act
+        let path = find_path(&(0, 0), is_goal, neighbors, key, manhattan, |_| {
+            expanded_with_heuristic += 1
+        });
+        let blind_path = find_path(
+            &(0, 0),
+            is_goal,
+            neighbors,
+            key,
+            |_| 0,
+            |_| expanded_without_heuristic += 1,
+        );
+
+        // Assert
+        assert_eq!(path.unwrap().len(), 5);
+        assert_eq!(blind_path.unwrap().len(), 5);
+        assert!(expanded_with_heuristic < expanded_without_heuristic);
+    }
+
+    #[test]
+    fn test_find_path_not_found() {
+        // Arrange
+        let is_goal = |&x: &i32| x == 10;
+        let neighbors = |&x: &i32| if x < 5 { vec![x + 1] } else { vec![] };
+
+        // Act
+        let path = find_path(&0, is_goal, neighbors, |&x| x, |_| 0, |_| {});
+
+        // Assert
+        assert_eq!(path, None);
+    }
+}
@@ -1,39 +1,66 @@
 use std::collections::VecDeque;
 use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// The number of expanded states between checks of the elapsed time, to limit the overhead.
+const TIMEOUT_CHECK_INTERVAL: usize = 4096;
 
 /// Represents a node in the search tree, containing the state and a reference to its previous node.
 #[derive(Debug)]
-struct Node<T> {
-    state: T,
+pub(crate) struct Node<T> {
+    pub(crate) state: T,
     previous: Option<Rc<Node<T>>>,
 }
 
 impl<T> Node<T> {
     /// Creates a new `Node` with the given state and previous node.
-    fn new(state: T, previous: Option<Rc<Node<T>>>) -> Rc<Self> {
+    pub(crate) fn new(state: T, previous: Option<Rc<Node<T>>>) -> Rc<Self> {
         Rc::new(Node { state, previous })
     }
 }
 
 /// A trait for types that can trace their path from the start state to the goal state.
-trait TracePath<T> {
+pub(crate) trait TracePath<T> {
     fn trace_path(&self) -> Vec<T>;
+    fn trace_path_into(&self, out: &mut Vec<T>);
 }
 
 impl<T: Clone> TracePath<T> for Rc<Node<T>> {
     /// Returns the path from the start state to the goal state.
     fn trace_path(&self) -> Vec<T> {
         let mut path = Vec::new();
-        let mut current_node = Some(self.clone());
+        self.trace_path_into(&mut path);
+        path
+    }
+
+    /// Writes the path from the start state to the goal state into `out`, replacing its contents.
+    ///
+    /// Walks the nodes by reference instead of cloning each `Rc`, and reuses the buffer of `out`.
+    fn trace_path_into(&self, out: &mut Vec<T>) {
+        out.clear();
+        let mut current_node = Some(self.as_ref());
         while let Some(node) = current_node {
-            path.push(node.state.clone());
-            current_node = node.previous.clone();
+            out.push(node.state.clone());
+            current_node = node.previous.as_deref();
         }
-        path.reverse();
-        path
+        out.reverse();
     }
 }
 
+/// The outcome of a breadth-first search that may be cut off by a node budget or a timeout.
+#[derive(Debug, PartialEq)]
+pub enum SearchOutcome<T> {
+    /// A shortest path from the start state to a goal state.
+    Found(Vec<T>),
+    /// The whole reachable space was explored without reaching a goal state.
+    NotFound,
+    /// The search visited more states than the budget allows.
+    BudgetExceeded,
+    /// The search took longer than the timeout allows.
+    TimedOut,
+}
+
 /// Finds a path from the start state to a goal state using a breadth-first search algorithm.
 ///
 /// - `start_state` is the initial state.
@@ -46,8 +73,132 @@ pub fn find_path<T, FGoal, FNext, FVisit>(
     start_state: &T,
     is_goal: FGoal,
     neighbors: FNext,
-    mut try_visit: FVisit,
+    try_visit: FVisit,
 ) -> Option<Vec<T>>
+where
+    T: Clone,
+    FGoal: Fn(&T) -> bool,
+    FNext: Fn(&T) -> Vec<T>,
+    FVisit: FnMut(&T, usize) -> bool,
+{
+    PathFinder::new().find_path(start_state, is_goal, neighbors, try_visit)
+}
+
+/// Finds a path like `find_path`, but gives up once more than `budget` states have been visited.
+///
+/// Returns `SearchOutcome::BudgetExceeded` if the budget runs out before the search completes.
+pub fn find_path_with_budget<T, FGoal, FNext, FVisit>(
+    start_state: &T,
+    is_goal: FGoal,
+    neighbors: FNext,
+    try_visit: FVisit,
+    budget: usize,
+) -> SearchOutcome<T>
+where
+    T: Clone,
+    FGoal: Fn(&T) -> bool,
+    FNext: Fn(&T) -> Vec<T>,
+    FVisit: FnMut(&T, usize) -> bool,
+{
+    find_path_with_limits(start_state, is_goal, neighbors, try_visit, budget, None)
+}
+
+/// Finds a path like `find_path`, but gives up once the search has taken longer than `timeout`.
+///
+/// The elapsed time is only checked every `TIMEOUT_CHECK_INTERVAL` expanded states.
+///
+/// Returns `SearchOutcome::TimedOut` if the time runs out before the search completes.
+pub fn find_path_with_timeout<T, FGoal, FNext, FVisit>(
+    start_state: &T,
+    is_goal: FGoal,
+    neighbors: FNext,
+    try_visit: FVisit,
+    timeout: Duration,
+) -> SearchOutcome<T>
+where
+    T: Clone,
+    FGoal: Fn(&T) -> bool,
+    FNext: Fn(&T) -> Vec<T>,
+    FVisit: FnMut(&T, usize) -> bool,
+{
+    find_path_with_limits(
+        start_state,
+        is_goal,
+        neighbors,
+        try_visit,
+        usize::MAX,
+        Some(timeout),
+    )
+}
+
+/// The queue of a breadth-first search: the nodes to expand, with their depths.
+type SearchQueue<T> = VecDeque<(Rc<Node<T>>, usize)>;
+
+/// A breadth-first path finder that keeps its queue between searches.
+///
+/// `find_path` allocates a new queue for each search. Reusing a `PathFinder` for repeated searches
+/// pays for growing the queue only once.
+pub struct PathFinder<T> {
+    queue: SearchQueue<T>,
+}
+
+impl<T> Default for PathFinder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PathFinder<T> {
+    /// Creates a new `PathFinder` with an empty queue.
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Finds a path from the start state to a goal state, with the same arguments as `find_path`.
+    ///
+    /// The queue is cleared first, so it can be reused for repeated searches.
+    pub fn find_path<FGoal, FNext, FVisit>(
+        &mut self,
+        start_state: &T,
+        is_goal: FGoal,
+        neighbors: FNext,
+        try_visit: FVisit,
+    ) -> Option<Vec<T>>
+    where
+        T: Clone,
+        FGoal: Fn(&T) -> bool,
+        FNext: Fn(&T) -> Vec<T>,
+        FVisit: FnMut(&T, usize) -> bool,
+    {
+        let outcome = search(
+            &mut self.queue,
+            start_state,
+            is_goal,
+            neighbors,
+            try_visit,
+            usize::MAX,
+            None,
+        );
+        match outcome {
+            SearchOutcome::Found(path) => Some(path),
+            SearchOutcome::NotFound | SearchOutcome::BudgetExceeded | SearchOutcome::TimedOut => {
+                None
+            }
+        }
+    }
+}
+
+/// Finds a path like `find_path`, giving up when either the budget or the timeout runs out.
+fn find_path_with_limits<T, FGoal, FNext, FVisit>(
+    start_state: &T,
+    is_goal: FGoal,
+    neighbors: FNext,
+    try_visit: FVisit,
+    budget: usize,
+    timeout: Option<Duration>,
+) -> SearchOutcome<T>
 where
     T: Clone,
     FGoal: Fn(&T) -> bool,
@@ -55,32 +206,135 @@ where
     FVisit: FnMut(&T, usize) -> bool,
 {
     let mut queue = VecDeque::new();
+    search(
+        &mut queue,
+        start_state,
+        is_goal,
+        neighbors,
+        try_visit,
+        budget,
+        timeout,
+    )
+}
+
+/// Runs the breadth-first search of `find_path_with_limits` with the given queue.
+fn search<T, FGoal, FNext, FVisit>(
+    queue: &mut SearchQueue<T>,
+    start_state: &T,
+    is_goal: FGoal,
+    neighbors: FNext,
+    mut try_visit: FVisit,
+    budget: usize,
+    timeout: Option<Duration>,
+) -> SearchOutcome<T>
+where
+    T: Clone,
+    FGoal: Fn(&T) -> bool,
+    FNext: Fn(&T) -> Vec<T>,
+    FVisit: FnMut(&T, usize) -> bool,
+{
+    let started_at = Instant::now();
+    queue.clear();
+    let mut visited_count: usize = 0;
+    let mut expanded_count: usize = 0;
 
     const START_DEPTH: usize = 0;
     if try_visit(start_state, START_DEPTH) {
+        visited_count += 1;
         let start_node = Node::new(start_state.clone(), None);
         if is_goal(start_state) {
             // Found immediately.
-            return Some(start_node.trace_path());
+            return SearchOutcome::Found(start_node.trace_path());
         }
         queue.push_back((start_node, START_DEPTH));
     }
 
     while let Some((current_node, current_depth)) = queue.pop_front() {
+        expanded_count += 1;
+        if expanded_count.is_multiple_of(TIMEOUT_CHECK_INTERVAL)
+            && timeout.is_some_and(|timeout| started_at.elapsed() > timeout)
+        {
+            // Ran out of time.
+            return SearchOutcome::TimedOut;
+        }
         let next_depth = current_depth + 1;
         for next_state in (neighbors)(&current_node.state) {
             if !try_visit(&next_state, next_depth) {
                 // Already visited.
                 continue;
             }
+            visited_count += 1;
+            if visited_count > budget {
+                // Ran out of budget.
+                return SearchOutcome::BudgetExceeded;
+            }
             let next_node = Node::new(next_state.clone(), Some(current_node.clone()));
             if is_goal(&next_state) {
                 // Found the goal state.
-                return Some(next_node.trace_path());
+                return SearchOutcome::Found(next_node.trace_path());
             }
             queue.push_back((next_node, next_depth));
         }
     }
+    SearchOutcome::NotFound
+}
+
+/// Finds a path like `find_path`, computing the neighbors of each depth level in parallel.
+///
+/// The next states of the whole frontier are computed with rayon, then visited serially in the same order
+/// as `find_path`, so `try_visit` needs no synchronization and the same path is found.
+#[cfg(feature = "parallel")]
+pub fn find_path_parallel<T, FGoal, FNext, FVisit>(
+    start_state: &T,
+    is_goal: FGoal,
+    neighbors: FNext,
+    mut try_visit: FVisit,
+) -> Option<Vec<T>>
+where
+    T: Clone + Send + Sync,
+    FGoal: Fn(&T) -> bool,
+    FNext: Fn(&T) -> Vec<T> + Sync,
+    FVisit: FnMut(&T, usize) -> bool,
+{
+    use rayon::prelude::*;
+
+    const START_DEPTH: usize = 0;
+    if !try_visit(start_state, START_DEPTH) {
+        return None;
+    }
+    let start_node = Node::new(start_state.clone(), None);
+    if is_goal(start_state) {
+        // Found immediately.
+        return Some(start_node.trace_path());
+    }
+
+    let mut frontier = vec![start_node];
+    let mut depth = START_DEPTH;
+    while !frontier.is_empty() {
+        depth += 1;
+        let states = frontier
+            .iter()
+            .map(|node| node.state.clone())
+            .collect::<Vec<_>>();
+        let next_states_list = states.par_iter().map(&neighbors).collect::<Vec<_>>();
+
+        let mut next_frontier = vec![];
+        for (current_node, next_states) in frontier.iter().zip(next_states_list) {
+            for next_state in next_states {
+                if !try_visit(&next_state, depth) {
+                    // Already visited.
+                    continue;
+                }
+                let next_node = Node::new(next_state, Some(current_node.clone()));
+                if is_goal(&next_node.state) {
+                    // Found the goal state.
+                    return Some(next_node.trace_path());
+                }
+                next_frontier.push(next_node);
+            }
+        }
+        frontier = next_frontier;
+    }
     None // Not Found.
 }
 
@@ -89,6 +343,50 @@ mod tests {
     use super::*;
     use std::collections::HashSet;
 
+    #[test]
+    fn test_trace_path_into() {
+        // Arrange
+        let node = (1..5).fold(Node::new(0, None), |previous, state| {
+            Node::new(state, Some(previous))
+        });
+        let mut out = vec![9, 9, 9, 9, 9, 9, 9];
+
+        // Act
+        node.trace_path_into(&mut out);
+
+        // Assert
+        assert_eq!(out, node.trace_path());
+        assert_eq!(out, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_path_finder_reused() {
+        // Arrange: Linear path 0 -> 1 -> 2 -> 3 -> 4, then branching path 0 -> 1 -> 3 or 0 -> 2 -> 4
+        let mut finder = PathFinder::new();
+        let linear = |&x: &i32| if x < 4 { vec![x + 1] } else { vec![] };
+        let branch = |&x: &i32| match x {
+            0 => vec![1, 2],
+            1 => vec![3],
+            2 => vec![4],
+            _ => vec![],
+        };
+
+        // Act
+        let mut visited = HashSet::new();
+        let linear_path =
+            finder.find_path(&0, |&x| x == 4, linear, |x: &i32, _| visited.insert(*x));
+        let mut visited = HashSet::new();
+        let branch_path =
+            finder.find_path(&0, |&x| x == 4, branch, |x: &i32, _| visited.insert(*x));
+        let mut visited = HashSet::new();
+        let not_found = finder.find_path(&0, |&x| x == 5, linear, |x: &i32, _| visited.insert(*x));
+
+        // Assert
+        assert_eq!(linear_path, Some(vec![0, 1, 2, 3, 4]));
+        assert_eq!(branch_path, Some(vec![0, 2, 4]));
+        assert_eq!(not_found, None);
+    }
+
     #[test]
     fn test_find_path_linear() {
         // Arrange: Linear path: 0 -> 1 -> 2 -> 3 -> 4
@@ -197,4 +495,61 @@ mod tests {
         // Assert
         assert_eq!(path, Some(vec![0]));
     }
+
+    #[test]
+    fn test_find_path_with_budget() {
+        // Arrange: Linear path: 0 -> 1 -> ... -> 10
+        let goal = 10;
+        let is_goal = |&x: &i32| x == goal;
+        let neighbors = |&x: &i32| if x < goal { vec![x + 1] } else { vec![] };
+
+        // Act: Enough budget
+        let mut visited = HashSet::new();
+        let try_visit = |x: &i32, _depth: usize| visited.insert(*x);
+        let outcome = find_path_with_budget(&0, is_goal, neighbors, try_visit, 11);
+        // Assert
+        assert_eq!(outcome, SearchOutcome::Found((0..=10).collect()));
+
+        // Act: Not enough budget
+        let mut visited = HashSet::new();
+        let try_visit = |x: &i32, _depth: usize| visited.insert(*x);
+        let outcome = find_path_with_budget(&0, is_goal, neighbors, try_visit, 5);
+        // Assert
+        assert_eq!(outcome, SearchOutcome::BudgetExceeded);
+    }
+
+    #[test]
+    fn test_find_path_with_timeout() {
+        // Arrange: Endless linear path: 0 -> 1 -> 2 -> ...
+        let is_goal = |_: &i32| false;
+        let neighbors = |&x: &i32| vec![x + 1];
+        let try_visit = |_: &i32, _depth: usize| true;
+
+        // Act
+        let outcome = find_path_with_timeout(&0, is_goal, neighbors, try_visit, Duration::ZERO);
+        // Assert
+        assert_eq!(outcome, SearchOutcome::TimedOut);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_find_path_parallel() {
+        // Arrange: Two routes to 10; the shorter one goes through 5
+        let start = 0;
+        let is_goal = |&x: &i32| x == 10;
+        let neighbors = |&x: &i32| match x {
+            0 => vec![1, 5],
+            1 => vec![2],
+            2 => vec![10],
+            5 => vec![10],
+            _ => vec![],
+        };
+        let mut visited = HashSet::new();
+        let try_visit = |x: &i32, _depth: usize| visited.insert(*x);
+
+        // Act
+        let path = find_path_parallel(&start, is_goal, neighbors, try_visit);
+        // Assert
+        assert_eq!(path, Some(vec![0, 5, 10]));
+    }
 }
@@ -0,0 +1,117 @@
+use crate::bfs::Node;
+use crate::bfs::TracePath;
+use crate::greedy::Candidate;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Finds a path with the lowest total cost from the start state to a goal state using Dijkstra's algorithm.
+///
+/// - `start_state` is the initial state.
+/// - `is_goal` is a function that checks if a given state is the goal state.
+/// - `neighbors` is a function that returns the next states of a given state.
+/// - `key` is a function that returns the key identifying equivalent states.
+/// - `cost` is a function that returns the cost of the move that led to a given state.
+///
+/// Returns an `Option<Vec<T>>` containing the path from the start state to the goal state if found, or `None` if no path exists.
+pub fn find_path<T, K, FGoal, FNext, FKey, FCost>(
+    start_state: &T,
+    is_goal: FGoal,
+    neighbors: FNext,
+    key: FKey,
+    cost: FCost,
+) -> Option<Vec<T>>
+where
+    T: Clone,
+    K: Eq + Hash,
+    FGoal: Fn(&T) -> bool,
+    FNext: Fn(&T) -> Vec<T>,
+    FKey: Fn(&T) -> K,
+    FCost: Fn(&T) -> usize,
+{
+    let mut open_list = BinaryHeap::new();
+    let mut best_costs = HashMap::new();
+    let mut order = 0;
+
+    best_costs.insert(key(start_state), 0);
+    open_list.push(Candidate {
+        score: 0,
+        order,
+        node: Node::new(start_state.clone(), None),
+    });
+
+    while let Some(Candidate { score, node, .. }) = open_list.pop() {
+        if best_costs
+            .get(&key(&node.state))
+            .is_some_and(|&best| best < score)
+        {
+            // A cheaper path to this state was already found.
+            continue;
+        }
+        if is_goal(&node.state) {
+            // Found the goal state.
+            return Some(node.trace_path());
+        }
+        for next_state in (neighbors)(&node.state) {
+            let next_score = score + cost(&next_state);
+            let next_key = key(&next_state);
+            if best_costs
+                .get(&next_key)
+                .is_some_and(|&best| best <= next_score)
+            {
+                // Not cheaper than the known path.
+                continue;
+            }
+            best_costs.insert(next_key, next_score);
+            order += 1;
+            open_list.push(Candidate {
+                score: next_score,
+                order,
+                node: Node::new(next_state, Some(node.clone())),
+            });
+        }
+    }
+    None // Not Found.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_path_lowest_cost() {
+        // Arrange: 0 -> 1 -> 3 costs 10 + 1; 0 -> 2 -> 4 -> 3 costs 1 + 1 + 1
+        let start = 0;
+        let goal = 3;
+        let is_goal = |&x: &i32| x == goal;
+        let neighbors = |&x: &i32| match x {
+            0 => vec![1, 2],
+            1 => vec![3],
+            2 => vec![4],
+            4 => vec![3],
+            _ => vec![],
+        };
+        let key = |&x: &i32| x;
+        let cost = |&x: &i32| if x == 1 { 10 } else { 1 };
+
+        // Act
+        let path = find_path(&start, is_goal, neighbors, key, cost);
+        // Assert
+        assert_eq!(path, Some(vec![0, 2, 4, 3]));
+    }
+
+    #[test]
+    fn test_find_path_not_found() {
+        // Arrange: No path to goal
+        let start = 0;
+        let is_goal = |&x: &i32| x == 4;
+        let neighbors = |&x: &i32| if x < 2 { vec![x + 1] } else { vec![] };
+        let key = |&x: &i32| x;
+        let cost = |_: &i32| 1;
+
+        // Act
+        let path = find_path(&start, is_goal, neighbors, key, cost);
+        // Assert
+        assert_eq!(path, None);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::bfs::Node;
+use crate::bfs::TracePath;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::rc::Rc;
+
+/// An entry in the open list, ordered so that the lowest score is popped first.
+pub(crate) struct Candidate<T> {
+    pub(crate) score: usize,
+    pub(crate) order: usize,
+    pub(crate) node: Rc<Node<T>>,
+}
+
+impl<T> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Candidate<T> {}
+
+impl<T> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Candidate<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // `BinaryHeap` is a max-heap, so reverse the order.
+        // Ties are broken by insertion order to keep the search deterministic.
+        (other.score, other.order).cmp(&(self.score, self.order))
+    }
+}
+
+/// Finds a path from the start state to a goal state using a greedy best-first search algorithm.
+///
+/// - `start_state` is the initial state.
+/// - `is_goal` is a function that checks if a given state is the goal state.
+/// - `neighbors` is a function that returns the next states of a given state.
+/// - `try_visit` is a function that returns `true` if the state should be visited (i.e., it is unvisited), or `false` otherwise.
+/// - `heuristic` is a function that estimates the remaining distance from a given state to the goal.
+///
+/// The path found is not guaranteed to be the shortest one.
+///
+/// Returns an `Option<Vec<T>>` containing the path from the start state to the goal state if found, or `None` if no path exists.
+pub fn find_path<T, FGoal, FNext, FVisit, FScore>(
+    start_state: &T,
+    is_goal: FGoal,
+    neighbors: FNext,
+    mut try_visit: FVisit,
+    heuristic: FScore,
+) -> Option<Vec<T>>
+where
+    T: Clone,
+    FGoal: Fn(&T) -> bool,
+    FNext: Fn(&T) -> Vec<T>,
+    FVisit: FnMut(&T) -> bool,
+    FScore: Fn(&T) -> usize,
+{
+    let mut open_list = BinaryHeap::new();
+    let mut order = 0;
+
+    if try_visit(start_state) {
+        let start_node = Node::new(start_state.clone(), None);
+        if is_goal(start_state) {
+            // Found immediately.
+            return Some(start_node.trace_path());
+        }
+        open_list.push(Candidate {
+            score: heuristic(start_state),
+            order,
+            node: start_node,
+        });
+    }
+
+    while let Some(Candidate { node, .. }) = open_list.pop() {
+        for next_state in (neighbors)(&node.state) {
+            if !try_visit(&next_state) {
+                // Already visited.
+                continue;
+            }
+            let next_node = Node::new(next_state.clone(), Some(node.clone()));
+            if is_goal(&next_state) {
+                // Found the goal state.
+                return Some(next_node.trace_path());
+            }
+            order += 1;
+            open_list.push(Candidate {
+                score: heuristic(&next_state),
+                order,
+                node: next_node,
+            });
+        }
+    }
+    None // Not Found.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_find_path_follows_heuristic() {
+        // Arrange: Two routes to 10; the heuristic prefers the long one via 20
+        let start = 0;
+        let goal = 10;
+        let is_goal = |&x: &i32| x == goal;
+        let neighbors = |&x: &i32| match x {
+            0 => vec![1, 20],
+            1 => vec![10],
+            20 => vec![21],
+            21 => vec![10],
+            _ => vec![],
+        };
+        let mut visited = HashSet::new();
+        let try_visit = |x: &i32| visited.insert(*x);
+        let heuristic = |&x: &i32| if x >= 20 { 0 } else { 1 };
+
+        // Act
+        let path = find_path(&start, is_goal, neighbors, try_visit, heuristic);
+        // Assert
+        assert_eq!(path, Some(vec![0, 20, 21, 10]));
+    }
+
+    #[test]
+    fn test_find_path_not_found() {
+        // Arrange: No path to goal
+        let start = 0;
+        let is_goal = |&x: &i32| x == 4;
+        let neighbors = |&x: &i32| if x < 2 { vec![x + 1] } else { vec![] };
+        let mut visited = HashSet::new();
+        let try_visit = |x: &i32| visited.insert(*x);
+        let heuristic = |&x: &i32| (4 - x) as usize;
+
+        // Act
+        let path = find_path(&start, is_goal, neighbors, try_visit, heuristic);
+        // Assert
+        assert_eq!(path, None);
+    }
+}
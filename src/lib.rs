@@ -1,9 +1,80 @@
+mod astar;
 mod bfs;
+mod dijkstra;
+mod greedy;
 mod solver;
 
+pub use bfs::PathFinder;
+pub use solver::BatchResult;
+pub use solver::BitPattern;
+pub use solver::Board;
+pub use solver::BoardDisplayOptions;
+pub use solver::BoardError;
+pub use solver::BoardKey;
+pub use solver::Direction;
+pub use solver::GoalCondition;
+pub use solver::GridPattern;
+pub use solver::Heuristic;
+pub use solver::KeyContext;
+pub use solver::LogError;
+pub use solver::MoveError;
 pub use solver::MovePath;
 pub use solver::Piece;
+pub use solver::RecentPieces;
 pub use solver::Rule;
 pub use solver::RuleError;
+pub use solver::RuleKey;
+pub use solver::SearchStats;
+pub use solver::Shape;
+pub use solver::SolveOutcome;
 pub use solver::State;
+pub use solver::StepDelta;
+pub use solver::animate;
+pub use solver::blocking_heuristic;
+pub use solver::build_goal_table;
+pub use solver::classify;
+pub use solver::classify_batch;
+pub use solver::count_by_axis;
+pub use solver::count_goal_piece_moves;
+pub use solver::count_reachable_states;
+pub use solver::dedupe_symmetric_solutions;
+pub use solver::format_piece_runs;
+pub use solver::goal_mask_at;
+pub use solver::goal_table_entries;
+pub use solver::goal_table_from_entries;
+pub use solver::hardest_goal;
+pub use solver::invert_path;
+pub use solver::layouts;
+pub use solver::min_blanks_to_solve;
+pub use solver::parse_log;
+pub use solver::random_walk;
+pub use solver::random_walk_with_rule;
+pub use solver::reachable_goal_positions;
+pub use solver::run_frames_rgba;
+pub use solver::run_log;
+pub use solver::run_svg_frames;
+pub use solver::solution_boards;
+pub use solver::solution_progress;
 pub use solver::solve;
+pub use solver::solve_all_shortest;
+pub use solver::solve_astar;
+pub use solver::solve_batch;
+pub use solver::solve_best_effort;
+pub use solver::solve_from;
+pub use solver::solve_from_prefix;
+pub use solver::solve_min_max_displacement;
+pub use solver::solve_n_times;
+#[cfg(feature = "parallel")]
+pub use solver::solve_parallel;
+pub use solver::solve_partial;
+pub use solver::solve_prefer_adjacent_blanks;
+pub use solver::solve_some_shortest;
+pub use solver::solve_weighted;
+pub use solver::solve_with_stats;
+pub use solver::solve_with_table;
+pub use solver::solve_with_timeout;
+pub use solver::step_deltas;
+pub use solver::symmetry_pairs;
+pub use solver::to_json;
+pub use solver::to_json_delta;
+pub use solver::verify_solution;
@@ -1,5 +1,17 @@
 use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use klotski::BatchResult;
+use klotski::BitPattern;
+use klotski::BoardKey;
+use klotski::Direction;
+use klotski::MovePath;
+use klotski::Piece;
+use klotski::Rule;
 use klotski::RuleError;
+use klotski::Shape;
+use std::io::BufRead;
+use std::io::Write;
 
 /// Command-line arguments for the Klotski solver.
 #[derive(Debug, Parser)]
@@ -9,10 +21,101 @@ struct Args {
     #[arg(default_value = "0x2113_2113_4556_4786_900a")]
     start_image: String,
     /// Goal position mask for large pieces.
-    #[arg(default_value = "0x0000_0000_0000_0ff0_0ff0")]
+    #[arg(default_value = DEFAULT_GOAL_MASK)]
     goal_mask: String,
+    /// The row and column of the goal for the top-left cell of the large piece, e.g. `3,1`, instead of GOAL_MASK.
+    #[arg(long, value_name = "ROW,COL")]
+    goal_at: Option<String>,
+    /// Only validates the arguments and prints the starting board, without solving.
+    #[arg(long)]
+    check: bool,
+    /// Prints how many times the large piece moves in the solution.
+    #[arg(long)]
+    piece_stats: bool,
+    /// Plays back the solution in the terminal, one board per step.
+    #[arg(long)]
+    animate: bool,
+    /// Plays the puzzle by hand, reading moves such as `7 D` from the standard input.
+    #[arg(long)]
+    interactive: bool,
+    /// Prints only the number of moves and the final board, without the steps.
+    #[arg(long)]
+    final_only: bool,
+    /// Explains the reason for each step of the solution.
+    #[arg(long)]
+    explain: bool,
+    /// Prints consecutive moves of the same piece on one line, with the number of steps.
+    #[arg(long)]
+    group_runs: bool,
+    /// The output format of the solution. It cannot be combined with the other options that print the solution.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        conflicts_with_all = ["animate", "final_only", "explain", "group_runs", "max_solutions", "piece_stats"]
+    )]
+    format: OutputFormat,
+    /// Prints the number of distinct boards reachable from the starting board, without solving.
+    #[arg(long)]
+    count_states: bool,
+    /// Solves each puzzle in the file, written as `START_IMAGE GOAL_MASK` on each line.
+    #[arg(long, value_name = "FILE")]
+    batch: Option<std::path::PathBuf>,
+    /// Prints up to this many different shortest solutions, at least one.
+    #[arg(
+        long,
+        value_name = "K",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    max_solutions: Option<usize>,
+    /// Scrambles the starting board with this many random moves before solving, printing the scrambled board.
+    #[arg(long, value_name = "N")]
+    scramble: Option<usize>,
+    /// The seed of the random moves of `--scramble`, which gives the same board for the same seed.
+    #[arg(long, value_name = "SEED", default_value_t = 0)]
+    seed: u64,
+    /// Prints nothing but errors, exiting with status 2 if the puzzle is unsolvable.
+    ///
+    /// With `--check`, `--count-states` or `normalize`, it only validates the arguments. With `--batch`, it exits
+    /// with status 2 unless every puzzle is solved, and with `--interactive`, unless the goal is reached.
+    #[arg(long)]
+    quiet: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
+/// Commands other than solving.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Prints the canonical form of the starting board, shared by its mirror image and symmetric relabelings.
+    Normalize {
+        /// Starting board state.
+        start_image: String,
+    },
+}
+
+/// The output formats of the solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Numbered steps such as `step 1: Move piece #7: Down`.
+    Text,
+    /// The steps as JSON, with the board after each step.
+    Json,
+    /// One move per line such as `7 D`, as read by `--interactive`.
+    Notation,
+    /// The board after each step as a grid, separated by blank lines.
+    Grid,
+}
+
+/// The goal mask used when none is given: the large piece at the bottom center.
+const DEFAULT_GOAL_MASK: &str = "0x0000_0000_0000_0ff0_0ff0";
+
+/// The exit status of `--quiet` when the puzzle is unsolvable, apart from 1 for invalid arguments.
+const UNSOLVABLE_EXIT_CODE: i32 = 2;
+
+/// The delay between the frames of `--animate`, in milliseconds.
+const ANIMATION_DELAY_MS: u64 = 500;
+
 /// Runs the Klotski solver with the provided arguments.
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
@@ -22,34 +125,845 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 /// Runs the Klotski solver with the provided arguments.
 fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
-    let rule = klotski::Rule::parse(&args.start_image, &args.goal_mask).unwrap_or_else(|e| {
-        let message = convert_error_to_string(e);
-        eprintln!("Error: {message}");
+    if let Some(Command::Normalize { start_image }) = &args.command {
+        let canonical = normalize(start_image).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        });
+        if !args.quiet {
+            println!("{canonical}");
+        }
+        return Ok(());
+    }
+
+    if let Some(file) = &args.batch {
+        let text = std::fs::read_to_string(file)?;
+        let all_solved = if args.quiet {
+            run_batch(&mut std::io::sink(), &text)?
+        } else {
+            run_batch(&mut std::io::stdout(), &text)?
+        };
+        if args.quiet && !all_solved {
+            std::process::exit(UNSOLVABLE_EXIT_CODE);
+        }
+        return Ok(());
+    }
+
+    let rule = parse_args_to_rule(args).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
         std::process::exit(1);
     });
 
-    let Some(path) = klotski::solve(&rule) else {
-        println!("path not found.");
+    let rule = match args.scramble {
+        Some(steps) => {
+            let rule = scramble(&rule, steps, args.seed);
+            if !args.quiet {
+                println!("scrambled start: 0x{}", rule.start.pattern.to_hex20());
+                println!("{}", rule.start);
+            }
+            rule
+        }
+        None => rule,
+    };
+
+    if args.check {
+        if !args.quiet {
+            println!("valid");
+            println!("{}", rule.start);
+        }
+        return Ok(());
+    }
+
+    if args.interactive {
+        let stdin = std::io::stdin();
+        let reached = if args.quiet {
+            play(&rule, stdin.lock(), &mut std::io::sink())?
+        } else {
+            play(&rule, stdin.lock(), &mut std::io::stdout())?
+        };
+        if args.quiet && !reached {
+            std::process::exit(UNSOLVABLE_EXIT_CODE);
+        }
+        return Ok(());
+    }
+
+    if args.count_states {
+        if !args.quiet {
+            let count = klotski::count_reachable_states(&rule.start);
+            println!("{count} reachable states.");
+        }
+        return Ok(());
+    }
+
+    if args.quiet {
+        if klotski::solve(&rule).is_none() {
+            std::process::exit(UNSOLVABLE_EXIT_CODE);
+        }
+        return Ok(());
+    }
+
+    if args.final_only {
+        run_final(&mut std::io::stdout(), &rule)?;
+        return Ok(());
+    }
+
+    if let Some(max_solutions) = args.max_solutions {
+        run_solutions(&mut std::io::stdout(), &rule, max_solutions)?;
+        return Ok(());
+    }
+
+    let (path, stats) = klotski::solve_with_stats(&rule);
+    let Some(path) = path else {
+        println!("path not found ({stats}).");
         return Ok(());
     };
 
+    if args.animate {
+        klotski::animate(&mut std::io::stdout(), &path, ANIMATION_DELAY_MS)?;
+    } else if args.explain {
+        write_explained_steps(&mut std::io::stdout(), &path, &rule)?;
+    } else if args.group_runs {
+        for line in klotski::format_piece_runs(&path) {
+            println!("{line}");
+        }
+    } else {
+        write_solution(&mut std::io::stdout(), &path, args.format)?;
+    }
+
+    if args.piece_stats {
+        let goal_piece = rule.goal_piece();
+        let goal_piece_moves = klotski::count_goal_piece_moves(&path, goal_piece);
+        println!("piece #{goal_piece} moves: {goal_piece_moves}");
+        let (horizontal, vertical) = klotski::count_by_axis(&path);
+        println!("horizontal moves: {horizontal}, vertical moves: {vertical}");
+    }
+    Ok(())
+}
+
+/// Writes each move of the solution path as a numbered step.
+fn write_steps<W: Write>(output: &mut W, path: &[klotski::State]) -> std::io::Result<()> {
     for (i, state) in path.iter().enumerate() {
         if let Some(piece) = state.piece {
             let p = &state.path;
-            println!("step {i}: Move piece #{piece}: {p}");
+            writeln!(output, "step {i}: Move piece #{piece}: {p}")?;
         }
     }
     Ok(())
 }
 
-fn convert_error_to_string(e: RuleError) -> &'static str {
-    match e {
-        RuleError::InvalidStartBoardHexLength => "START_IMAGE must fit in 20 hex digits.",
-        RuleError::StartBoardInvalidEmptyCount => "START_IMAGE must have only two empty spaces.",
-        RuleError::FirstPieceMissingInStartBoard => "START_IMAGE must have the #1 large piece.",
-        RuleError::InvalidPieceShape => "START_IMAGE contains an invalid piece shape.",
-        RuleError::InvalidGoalMaskHexLength => "GOAL_MASK must fit in 20 hex digits.",
-        RuleError::GoalmaskInvalidError => "GOAL_MASK is an invalid mask for the goal positions.",
-        RuleError::GoalMaskShapeError => "GOAL_MASK has an invalid shape.",
+/// Writes the solution path in the format.
+fn write_solution<W: Write>(
+    output: &mut W,
+    path: &[klotski::State],
+    format: OutputFormat,
+) -> std::io::Result<()> {
+    match format {
+        OutputFormat::Text => write_steps(output, path),
+        OutputFormat::Json => writeln!(output, "{}", klotski::to_json(path)),
+        OutputFormat::Notation => write_notation(output, path),
+        OutputFormat::Grid => write_grids(output, path),
+    }
+}
+
+/// Writes each move of the solution path as the piece ID and directions, e.g. `9 R R`.
+///
+/// This is the format of the moves read by `play`, so the output can be played back with `--interactive`.
+fn write_notation<W: Write>(output: &mut W, path: &[klotski::State]) -> std::io::Result<()> {
+    for state in path {
+        if let Some(piece) = state.piece {
+            let directions = state
+                .path
+                .directions()
+                .iter()
+                .map(|direction| match direction {
+                    Direction::Up => "U",
+                    Direction::Down => "D",
+                    Direction::Left => "L",
+                    Direction::Right => "R",
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(output, "{piece} {directions}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes each board of the solution path from the start as a grid, followed by a blank line.
+fn write_grids<W: Write>(output: &mut W, path: &[klotski::State]) -> std::io::Result<()> {
+    for state in path {
+        writeln!(output, "{}", state.board)?;
+        writeln!(output)?;
+    }
+    Ok(())
+}
+
+/// Writes each move of the solution path as a numbered step, followed by the reason for it.
+fn write_explained_steps<W: Write>(
+    output: &mut W,
+    path: &[klotski::State],
+    rule: &Rule,
+) -> std::io::Result<()> {
+    for (i, pair) in path.windows(2).enumerate() {
+        let explanation = pair[1].explain(&pair[0].board, rule);
+        writeln!(output, "step {}: {explanation}", i + 1)?;
+    }
+    Ok(())
+}
+
+/// Solves the puzzle, printing up to `max_solutions` different shortest solutions.
+///
+/// Each solution is headed by `solution N:` and followed by a blank line.
+fn run_solutions<W: Write>(
+    output: &mut W,
+    rule: &Rule,
+    max_solutions: usize,
+) -> std::io::Result<()> {
+    let paths = klotski::solve_some_shortest(rule, true, max_solutions);
+    if paths.is_empty() {
+        return writeln!(output, "path not found.");
+    }
+    for (i, path) in paths.iter().enumerate() {
+        writeln!(output, "solution {}:", i + 1)?;
+        write_steps(output, path)?;
+        writeln!(output)?;
+    }
+    Ok(())
+}
+
+/// Solves each puzzle of the batch, printing a numbered summary line for each.
+///
+/// Returns true if every puzzle was solved.
+fn run_batch<W: Write>(output: &mut W, text: &str) -> std::io::Result<bool> {
+    let results = klotski::solve_batch(text.lines());
+    for (i, result) in results.iter().enumerate() {
+        writeln!(output, "{}: {result}", i + 1)?;
+    }
+    Ok(results
+        .iter()
+        .all(|result| matches!(result, BatchResult::Solved(_))))
+}
+
+/// Solves the puzzle, printing only the number of moves and the final board.
+fn run_final<W: Write>(output: &mut W, rule: &Rule) -> std::io::Result<()> {
+    match klotski::solve(rule) {
+        Some(path) => {
+            writeln!(output, "solvable in {} moves.", path.len() - 1)?;
+            writeln!(output, "{}", path.last().unwrap().board)
+        }
+        None => writeln!(output, "path not found."),
+    }
+}
+
+/// Plays the puzzle interactively, reading one move per line from `input` until the goal is reached.
+///
+/// A move is the piece ID followed by one or two directions, each `U`, `D`, `L` or `R`, e.g. `7 D` or `9 R R`.
+/// Illegal moves are rejected and leave the board unchanged. `q` quits.
+///
+/// Returns true if the goal was reached.
+fn play<R: BufRead, W: Write>(rule: &Rule, input: R, output: &mut W) -> std::io::Result<bool> {
+    let mut board = rule.start.clone();
+    let mut moves = 0;
+    writeln!(output, "{board}")?;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "q" {
+            break;
+        }
+        let Some((piece, path)) = parse_move(line) else {
+            writeln!(output, "invalid input: {line}")?;
+            continue;
+        };
+        let Some(next_board) = board.apply(piece, &path) else {
+            writeln!(output, "illegal move: {line}")?;
+            continue;
+        };
+        board = next_board;
+        moves += 1;
+        writeln!(output, "{board}")?;
+        if rule.is_finished(&board) {
+            writeln!(output, "goal reached in {moves} moves.")?;
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Parses a move such as `7 D` into the piece and its path.
+fn parse_move(line: &str) -> Option<(Piece, MovePath)> {
+    let mut words = line.split_whitespace();
+    let piece = Piece::try_new(u8::from_str_radix(words.next()?, 16).ok()?)?;
+    let directions = words
+        .map(|word| match word.to_ascii_uppercase().as_str() {
+            "U" => Some(Direction::Up),
+            "D" => Some(Direction::Down),
+            "L" => Some(Direction::Left),
+            "R" => Some(Direction::Right),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    match directions[..] {
+        [d] => Some((piece, MovePath::One(d))),
+        [d1, d2] => Some((piece, MovePath::Two(d1, d2))),
+        _ => None,
+    }
+}
+
+/// Returns the canonical form of the starting board as 20 hex digits, the same for all equivalent boards.
+///
+/// Symmetric pieces are paired as for the default goal, which is left-to-right symmetric.
+fn normalize(start_image: &str) -> Result<String, RuleError> {
+    let rule = Rule::parse(start_image, DEFAULT_GOAL_MASK)?;
+    let canonical = BoardKey::create(&rule, &rule.start).get_pattern();
+    let hex20 = format!("{:0>20x}", canonical.get_u128());
+    let rows = hex20
+        .as_bytes()
+        .chunks(4)
+        .map(|row| String::from_utf8_lossy(row));
+    Ok(format!("0x{}", rows.collect::<Vec<_>>().join("_")))
+}
+
+/// Parses the arguments into a rule, returning the error if they are invalid.
+fn parse_args_to_rule(args: &Args) -> Result<Rule, RuleError> {
+    match &args.goal_at {
+        Some(goal_at) => {
+            let goal_mask = parse_goal_at(goal_at).ok_or(RuleError::InvalidGoalPosition)?;
+            Rule::parse(&args.start_image, &format!("0x{}", goal_mask.to_hex20()))
+        }
+        None => Rule::parse(&args.start_image, &args.goal_mask),
+    }
+}
+
+/// Returns the rule with its starting board scrambled by `steps` random moves, keeping the goal and options.
+///
+/// The symmetry pairs depend on the starting board, so they are found again, leaving out the pairs that
+/// the piece goals or the locked pieces rule out, as `Rule::with_piece_goals` and `Rule::with_locked` do.
+/// Since every move can be undone, the scrambled puzzle is solvable whenever the original one is.
+fn scramble(rule: &Rule, steps: usize, seed: u64) -> Rule {
+    let walk = klotski::random_walk_with_rule(rule, steps, seed);
+    let start = walk.last().map_or(&rule.start, |state| &state.board);
+    let mut pairs = match rule.piece_goals.is_empty() {
+        true => klotski::symmetry_pairs(start, &rule.goal.mask()),
+        false => vec![],
+    };
+    pairs.retain(|(p, q)| !rule.locked.contains(p) && !rule.locked.contains(q));
+    Rule {
+        start: start.clone(),
+        pairs,
+        ..rule.clone()
+    }
+}
+
+/// Parses a `ROW,COL` goal position into the goal mask of the large piece.
+fn parse_goal_at(goal_at: &str) -> Option<BitPattern> {
+    let (row, col) = goal_at.split_once(',')?;
+    let row = row.trim().parse().ok()?;
+    let col = col.trim().parse().ok()?;
+    klotski::goal_mask_at(row, col, Shape::Large)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_valid_board() {
+        // Arrange
+        let args = Args::try_parse_from(["klotski", "--check"]).unwrap();
+        // Act
+        let result = parse_args_to_rule(&args);
+        // Assert
+        assert!(args.check);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_check_invalid_board() {
+        // Arrange
+        let args =
+            Args::try_parse_from(["klotski", "--check", "0x2113_2113_4556_4786_9aaa"]).unwrap();
+        // Act
+        let result = parse_args_to_rule(&args);
+        // Assert
+        let error = result.unwrap_err();
+        assert_eq!(
+            error,
+            RuleError::StartBoardInvalidEmptyCount {
+                found: 0,
+                expected: 2
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "START_IMAGE must have only two empty spaces."
+        );
+    }
+
+    #[test]
+    fn test_parse_args_to_rule_errors() {
+        // Arrange
+        let cases = [
+            (
+                [
+                    "klotski",
+                    "0x2113_2113_4556_4786_900a_0",
+                    "0x0000_0000_0000_0ff0_0ff0",
+                ],
+                RuleError::InvalidStartBoardHexLength,
+            ),
+            (
+                [
+                    "klotski",
+                    "0x2223_2223_4556_4786_900a",
+                    "0x0000_0000_0000_0ff0_0ff0",
+                ],
+                RuleError::FirstPieceMissingInStartBoard,
+            ),
+            (
+                [
+                    "klotski",
+                    "0x2113_2113_4556_4780_9a0a",
+                    "0x0000_0000_0000_0ff0_0ff0",
+                ],
+                RuleError::InvalidPieceShape {
+                    piece: klotski::Piece::new(0xa),
+                },
+            ),
+            (
+                [
+                    "klotski",
+                    "0x2113_2113_0665_5789_ab0c",
+                    "0x0000_0000_0000_0ff0_0ff0",
+                ],
+                RuleError::InvalidPieceShape {
+                    piece: klotski::Piece::new(5),
+                },
+            ),
+            (
+                [
+                    "klotski",
+                    "0x2113_2113_4556_4786_900a",
+                    "0x1_0000_0000_0000_0ff0_0ff0",
+                ],
+                RuleError::InvalidGoalMaskHexLength,
+            ),
+            (
+                [
+                    "klotski",
+                    "0x2113_2113_4556_4786_900a",
+                    "0x0000_0000_0000_0ff0_0fff",
+                ],
+                RuleError::GoalmaskInvalidError,
+            ),
+            (
+                [
+                    "klotski",
+                    "0x2113_2113_4556_4786_900a",
+                    "0x0000_0000_0000_ff00_0ff0",
+                ],
+                RuleError::GoalMaskShapeError,
+            ),
+        ];
+
+        for (argv, expected) in cases {
+            // Act
+            let args = Args::try_parse_from(argv).unwrap();
+            let result = parse_args_to_rule(&args);
+
+            // Assert
+            assert_eq!(result.err(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_play_to_goal() {
+        // Arrange
+        let args = Args::try_parse_from([
+            "klotski",
+            "--interactive",
+            "0x2113_2113_4556_4786_900a",
+            "0xff00_ff00_0000_0000_0000",
+        ])
+        .unwrap();
+        let rule = parse_args_to_rule(&args).unwrap();
+        let solution = klotski::solve(&rule).unwrap();
+        let letter = |direction: &Direction| match direction {
+            Direction::Up => "U",
+            Direction::Down => "D",
+            Direction::Left => "L",
+            Direction::Right => "R",
+        };
+        let mut script = vec!["1 D".to_string(), "x".to_string(), "11 D".to_string()];
+        script.extend(solution.iter().skip(1).map(|state| {
+            let directions = state.path.directions();
+            let letters = directions.iter().map(letter).collect::<Vec<_>>();
+            format!("{} {}", state.piece.unwrap(), letters.join(" "))
+        }));
+        let input = script.join("\n");
+        let mut output = vec![];
+
+        // Act
+        let reached = play(&rule, input.as_bytes(), &mut output).unwrap();
+
+        // Assert
+        let output = String::from_utf8(output).unwrap();
+        assert!(args.interactive);
+        assert!(reached);
+        assert!(output.contains("illegal move: 1 D\n"));
+        assert!(output.contains("invalid input: x\n"));
+        assert!(output.contains("invalid input: 11 D\n"));
+        assert!(output.ends_with(&format!(
+            "{}\ngoal reached in {} moves.\n",
+            solution.last().unwrap().board,
+            solution.len() - 1
+        )));
+    }
+
+    #[test]
+    fn test_normalize_mirrored_board() {
+        // Arrange
+        let args =
+            Args::try_parse_from(["klotski", "normalize", "0x2113_2113_4556_4786_900a"]).unwrap();
+        let Some(Command::Normalize { start_image }) = args.command else {
+            panic!("expected the normalize command");
+        };
+
+        // Act
+        let canonical = normalize(&start_image).unwrap();
+        let mirrored = normalize("0x3112_3112_6554_6874_a009").unwrap();
+
+        // Assert
+        assert_eq!(canonical, mirrored);
+        assert_eq!(canonical.len(), "0x".len() + 24);
+        assert!(normalize("0x2113_2113_4556_4786_9aaa").is_err());
+    }
+
+    #[test]
+    fn test_run_final() {
+        // Arrange
+        let args = Args::try_parse_from([
+            "klotski",
+            "--final-only",
+            "0x2113_2113_4556_4786_900a",
+            "0xff00_ff00_0000_0000_0000",
+        ])
+        .unwrap();
+        let rule = parse_args_to_rule(&args).unwrap();
+        let mut output = vec![];
+
+        // Act
+        run_final(&mut output, &rule).unwrap();
+
+        // Assert
+        let path = klotski::solve(&rule).unwrap();
+        let expected = format!(
+            "solvable in {} moves.\n{}\n",
+            path.len() - 1,
+            path.last().unwrap().board
+        );
+        assert!(args.final_only);
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_run_final_unsolvable() {
+        // Arrange
+        let args = Args::try_parse_from(["klotski", "--final-only", "0x2113_2113_4455_6677_8009"])
+            .unwrap();
+        let rule = parse_args_to_rule(&args).unwrap();
+        let mut output = vec![];
+
+        // Act
+        run_final(&mut output, &rule).unwrap();
+
+        // Assert
+        assert_eq!(String::from_utf8(output).unwrap(), "path not found.\n");
+    }
+
+    #[test]
+    fn test_write_explained_steps() {
+        // Arrange
+        let args = Args::try_parse_from([
+            "klotski",
+            "--explain",
+            "0x2113_2113_4556_4786_900a",
+            "0xff00_ff00_0000_0000_0000",
+        ])
+        .unwrap();
+        let rule = parse_args_to_rule(&args).unwrap();
+        let path = klotski::solve(&rule).unwrap();
+        let mut output = vec![];
+
+        // Act
+        write_explained_steps(&mut output, &path, &rule).unwrap();
+
+        // Assert
+        let output = String::from_utf8(output).unwrap();
+        assert!(args.explain);
+        assert_eq!(output.lines().count(), path.len() - 1);
+        assert!(output.starts_with("step 1: moves piece #"));
+        assert!(output.ends_with("into the goal\n"));
+    }
+
+    #[test]
+    fn test_goal_at() {
+        // Arrange
+        let args = Args::try_parse_from(["klotski", "--goal-at", "3,1"]).unwrap();
+        let default_args = Args::try_parse_from(["klotski"]).unwrap();
+
+        // Act
+        let rule = parse_args_to_rule(&args).unwrap();
+
+        // Assert
+        assert_eq!(rule.goal, parse_args_to_rule(&default_args).unwrap().goal);
+        for goal_at in ["4,1", "3", "a,b", "1,-1"] {
+            let args = Args::try_parse_from(["klotski", "--goal-at", goal_at]).unwrap();
+            assert_eq!(
+                parse_args_to_rule(&args).unwrap_err(),
+                RuleError::InvalidGoalPosition
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_args_to_rule_pathological() {
+        // Arrange: Strings that must be rejected without panicking
+        let huge = "1".repeat(100_000);
+        let strings = [
+            "",
+            "0x",
+            "____",
+            "é",
+            "0xé",
+            "０ｘ２１１３",
+            "\u{0}",
+            "18446744073709551615,18446744073709551615",
+            "99999999999999999999999,0",
+            ",",
+            "1,,1",
+            huge.as_str(),
+        ];
+
+        for s in strings {
+            // Act
+            let args = [
+                Args::try_parse_from(["klotski", s]).unwrap(),
+                Args::try_parse_from(["klotski", "0x2113_2113_4556_4786_900a", s]).unwrap(),
+                Args::try_parse_from(["klotski", "--goal-at", s]).unwrap(),
+            ];
+
+            // Assert
+            for args in &args {
+                assert!(parse_args_to_rule(args).is_err(), "{s:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_scramble() {
+        // Arrange
+        let args = Args::try_parse_from([
+            "klotski",
+            "--goal-at",
+            "0,0",
+            "--scramble",
+            "20",
+            "--seed",
+            "7",
+        ])
+        .unwrap();
+        let rule = parse_args_to_rule(&args).unwrap();
+
+        // Act
+        let scrambled = scramble(&rule, args.scramble.unwrap(), args.seed);
+
+        // Assert
+        assert_ne!(scrambled.start, rule.start);
+        assert_eq!(scrambled.goal, rule.goal);
+        assert_eq!(scrambled.start, scramble(&rule, 20, 7).start);
+        let path = klotski::solve(&scrambled).unwrap();
+        assert!(klotski::verify_solution(&scrambled, &path));
+        assert!(scrambled.is_finished(&path.last().unwrap().board));
+    }
+
+    #[test]
+    fn test_scramble_keeps_options() {
+        // Arrange
+        let args = Args::try_parse_from(["klotski", "0x2113_2113_4556_4786_900a"]).unwrap();
+        let mut rule = parse_args_to_rule(&args).unwrap();
+        rule.tabu_window = 2;
+        rule.allow_reversal = true;
+        rule.weights.insert(Piece::new(7), 3);
+        rule.direction_order.reverse();
+
+        // Act
+        let scrambled = scramble(&rule, 20, 7);
+
+        // Assert
+        assert_ne!(scrambled.start, rule.start);
+        assert_eq!(scrambled.tabu_window, 2);
+        assert!(scrambled.allow_reversal);
+        assert_eq!(scrambled.weights, rule.weights);
+        assert_eq!(scrambled.direction_order, rule.direction_order);
+        // The pairs are found in no particular order.
+        let mut pairs = scrambled.pairs.clone();
+        pairs.sort();
+        let mut expected_pairs = klotski::symmetry_pairs(&scrambled.start, &rule.goal.mask());
+        expected_pairs.sort();
+        assert_eq!(pairs, expected_pairs);
+    }
+
+    #[test]
+    fn test_scramble_keeps_locked_pieces() {
+        // Arrange
+        let start = klotski::Board::new(0x2113_2113_4556_4786_900a);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let locked = Piece::new(7);
+        let rule = Rule::with_locked(&start, &goal_mask, vec![locked]).unwrap();
+
+        // Act
+        let scrambled = scramble(&rule, 50, 7);
+
+        // Assert
+        assert_ne!(scrambled.start, rule.start);
+        assert_eq!(scrambled.locked, vec![locked]);
+        assert_eq!(
+            scrambled.start.pattern.mask_of(locked),
+            start.pattern.mask_of(locked)
+        );
+        assert!(
+            scrambled
+                .pairs
+                .iter()
+                .all(|&(p, q)| p != locked && q != locked)
+        );
+    }
+
+    #[test]
+    fn test_format_conflicts_with_other_outputs() {
+        // Arrange
+        let others = [
+            vec!["--animate"],
+            vec!["--final-only"],
+            vec!["--explain"],
+            vec!["--group-runs"],
+            vec!["--max-solutions", "2"],
+            vec!["--piece-stats"],
+        ];
+
+        for other in others {
+            // Act
+            let with_format =
+                Args::try_parse_from([&["klotski", "--format", "json"], &other[..]].concat());
+            let without_format = Args::try_parse_from([&["klotski"], &other[..]].concat());
+
+            // Assert
+            assert_eq!(
+                with_format.unwrap_err().kind(),
+                clap::error::ErrorKind::ArgumentConflict
+            );
+            assert!(without_format.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_run_batch() {
+        // Arrange
+        let text = "0x2113_2113_4556_4786_900a 0xff00_ff00_0000_0000_0000\n\nnot a puzzle\n";
+        let mut output = vec![];
+
+        // Act
+        let all_solved = run_batch(&mut output, text).unwrap();
+
+        // Assert
+        assert!(!all_solved);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "1: solvable in 7 moves.\n2: Error: expected START_IMAGE and GOAL_MASK.\n"
+        );
+    }
+
+    #[test]
+    fn test_run_solutions() {
+        // Arrange
+        let args = Args::try_parse_from([
+            "klotski",
+            "--max-solutions",
+            "2",
+            "0x2113_2113_4556_4786_900a",
+            "0xff00_ff00_0000_0000_0000",
+        ])
+        .unwrap();
+        let rule = parse_args_to_rule(&args).unwrap();
+        let max_solutions = args.max_solutions.unwrap();
+        let mut output = vec![];
+
+        // Act
+        run_solutions(&mut output, &rule, max_solutions).unwrap();
+
+        // Assert
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "solution 1:\n\
+             step 1: Move piece #7: Down\n\
+             step 2: Move piece #a: Left\n\
+             step 3: Move piece #6: Down\n\
+             step 4: Move piece #5: Right\n\
+             step 5: Move piece #4: Right\n\
+             step 6: Move piece #2: Down and Down\n\
+             step 7: Move piece #1: Left\n\
+             \n\
+             solution 2:\n\
+             step 1: Move piece #a: Left\n\
+             step 2: Move piece #6: Down\n\
+             step 3: Move piece #5: Right\n\
+             step 4: Move piece #7: Down\n\
+             step 5: Move piece #4: Right\n\
+             step 6: Move piece #2: Down and Down\n\
+             step 7: Move piece #1: Left\n\
+             \n"
+        );
+        for path in klotski::solve_some_shortest(&rule, true, max_solutions) {
+            assert!(klotski::verify_solution(&rule, &path));
+        }
+    }
+
+    #[test]
+    fn test_max_solutions_rejects_zero() {
+        // Act
+        let zero = Args::try_parse_from(["klotski", "--max-solutions", "0"]);
+        let one = Args::try_parse_from(["klotski", "--max-solutions", "1"]);
+
+        // Assert
+        assert_eq!(
+            zero.unwrap_err().kind(),
+            clap::error::ErrorKind::ValueValidation
+        );
+        assert_eq!(one.unwrap().max_solutions, Some(1));
+    }
+
+    #[test]
+    fn test_goal_that_cannot_hold_large_piece() {
+        // Arrange: Four cells in a row, which the 2x2 large piece can never occupy
+        let args = Args::try_parse_from([
+            "klotski",
+            "0x2113_2113_4556_4786_900a",
+            "0x0000_0000_0000_0000_ffff",
+        ])
+        .unwrap();
+
+        // Act
+        let error = parse_args_to_rule(&args).unwrap_err();
+
+        // Assert
+        assert_eq!(error, RuleError::GoalMaskShapeError);
+        assert_eq!(
+            error.to_string(),
+            "GOAL_MASK must be a 2x2 square of `f` digits to hold the #1 large piece."
+        );
     }
 }
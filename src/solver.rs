@@ -1,20 +1,98 @@
+mod animation;
+mod batch;
 mod bit_pattern;
+mod blanks;
 mod board;
 mod direction;
+mod game_log;
+mod goal_condition;
+mod goal_table;
+mod heuristic;
+mod json;
+pub mod layouts;
 mod move_path;
+mod opening_book;
 mod piece;
+mod random_walk;
+mod reachable;
 mod rule;
+mod search_stats;
+mod shape;
 mod solve;
+mod solve_outcome;
 mod state;
 mod visited_history;
 
+pub use animation::animate;
+pub use animation::run_frames_rgba;
+pub use animation::run_svg_frames;
+pub use batch::BatchResult;
+pub use batch::classify_batch;
+pub use batch::solve_batch;
 pub use bit_pattern::BitPattern;
+pub use bit_pattern::GridPattern;
+pub use blanks::min_blanks_to_solve;
 pub use board::Board;
+pub use board::BoardDisplayOptions;
+pub use board::BoardError;
+pub use board::MoveError;
 pub use direction::Direction;
+pub use game_log::LogError;
+pub use game_log::parse_log;
+pub use game_log::run_log;
+pub use goal_condition::GoalCondition;
+pub use goal_table::build_goal_table;
+pub use goal_table::goal_table_entries;
+pub use goal_table::goal_table_from_entries;
+pub use goal_table::solve_with_table;
+pub use heuristic::Heuristic;
+pub use heuristic::blocking_heuristic;
+pub use heuristic::solution_progress;
+pub use json::StepDelta;
+pub use json::step_deltas;
+pub use json::to_json;
+pub use json::to_json_delta;
 pub use move_path::MovePath;
 pub use piece::Piece;
+pub use random_walk::random_walk;
+pub use random_walk::random_walk_with_rule;
+pub use reachable::count_reachable_states;
+pub use reachable::hardest_goal;
+pub use reachable::reachable_goal_positions;
 pub use rule::Rule;
 pub use rule::RuleError;
+pub use rule::RuleKey;
+pub use rule::symmetry_pairs;
+pub use search_stats::SearchStats;
+pub use shape::Shape;
+pub use shape::classify;
+pub use shape::goal_mask_at;
+pub use solve::BoardKey;
+pub use solve::KeyContext;
 pub use solve::solve;
+pub use solve::solve_all_shortest;
+pub use solve::solve_astar;
+pub use solve::solve_best_effort;
+pub use solve::solve_from;
+pub use solve::solve_from_prefix;
+pub use solve::solve_min_max_displacement;
+pub use solve::solve_n_times;
+#[cfg(feature = "parallel")]
+pub use solve::solve_parallel;
+pub use solve::solve_partial;
+pub use solve::solve_prefer_adjacent_blanks;
+pub use solve::solve_some_shortest;
+pub use solve::solve_weighted;
+pub use solve::solve_with_stats;
+pub use solve::solve_with_timeout;
+pub use solve::verify_solution;
+pub use solve_outcome::SolveOutcome;
+pub use state::RecentPieces;
 pub use state::State;
+pub use state::count_by_axis;
+pub use state::count_goal_piece_moves;
+pub use state::dedupe_symmetric_solutions;
+pub use state::format_piece_runs;
+pub use state::invert_path;
+pub use state::solution_boards;
 use visited_history::VisitedHistory;
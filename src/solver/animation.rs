@@ -0,0 +1,121 @@
+use super::Rule;
+use super::State;
+use super::solve;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+/// The ANSI escape sequence that clears the screen and moves the cursor to the top-left corner.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Plays back the solution path in a terminal, redrawing the board for each step.
+///
+/// Each frame clears the screen, then prints the step and the board, and waits `delay_ms` milliseconds.
+pub fn animate<W: Write>(output: &mut W, path: &[State], delay_ms: u64) -> std::io::Result<()> {
+    for (i, state) in path.iter().enumerate() {
+        write!(output, "{CLEAR_SCREEN}")?;
+        match state.piece {
+            Some(piece) => writeln!(output, "step {i}: Move piece #{piece}: {}", state.path)?,
+            None => writeln!(output, "start")?,
+        }
+        writeln!(output, "{}", state.board)?;
+        output.flush()?;
+        if delay_ms > 0 {
+            thread::sleep(Duration::from_millis(delay_ms));
+        }
+    }
+    Ok(())
+}
+
+/// Renders each board of the solution path as an SVG image, as drawn by `Board::to_svg`.
+///
+/// Returns one SVG document per step, starting with the initial board.
+pub fn run_svg_frames(path: &[State], cell_px: u32) -> Vec<String> {
+    path.iter()
+        .map(|state| state.board.to_svg(cell_px))
+        .collect()
+}
+
+/// Solves the puzzle and renders each board of the solution as raw RGBA pixels, as drawn by `Board::to_rgba`.
+///
+/// Returns one buffer per step, starting with the initial board, for an image encoder such as one
+/// writing an animated GIF. Returns no frames if the puzzle is unsolvable.
+pub fn run_frames_rgba(rule: &Rule, cell_px: u32) -> Vec<Vec<u8>> {
+    let Some(path) = solve(rule) else {
+        return vec![];
+    };
+    path.iter()
+        .map(|state| state.board.to_rgba(cell_px))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_animate() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let path = solve(&rule).unwrap();
+        let mut output = vec![];
+
+        // Act
+        animate(&mut output, &path, 0).unwrap();
+
+        // Assert
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches(CLEAR_SCREEN).count(), path.len());
+        assert!(output.ends_with(&format!("{}\n", path.last().unwrap().board)));
+    }
+
+    #[test]
+    fn test_run_svg_frames() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let path = solve(&rule).unwrap();
+
+        // Act
+        let frames = run_svg_frames(&path, 32);
+
+        // Assert
+        assert_eq!(frames.len(), path.len());
+        for (frame, state) in frames.iter().zip(&path) {
+            assert_eq!(*frame, state.board.to_svg(32));
+            assert_eq!(frame.matches("<rect").count(), 5);
+        }
+    }
+
+    #[test]
+    fn test_run_frames_rgba() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let path = solve(&rule).unwrap();
+        let (width, height) = (4 * 16, 5 * 16);
+
+        // Act
+        let frames = run_frames_rgba(&rule, 16);
+
+        // Assert
+        assert_eq!(frames.len(), path.len());
+        for (frame, state) in frames.iter().zip(&path) {
+            assert_eq!(frame.len(), width * height * 4);
+            assert_eq!(*frame, state.board.to_rgba(16));
+        }
+        let unsolvable = Rule::new(
+            &Board::new(0x2113_2113_4455_6677_8009),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        assert!(run_frames_rgba(&unsolvable, 16).is_empty());
+    }
+}
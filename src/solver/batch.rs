@@ -0,0 +1,140 @@
+use super::Rule;
+use super::RuleError;
+use super::SolveOutcome;
+use super::solve::solve;
+
+/// The outcome of solving one puzzle of a batch.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BatchResult {
+    /// The puzzle was solved in the given number of moves.
+    Solved(usize),
+    /// The puzzle has no solution.
+    Unsolvable,
+    /// The line is not a starting board and a goal mask separated by whitespace.
+    Malformed,
+    /// The starting board or the goal mask is invalid.
+    Invalid(RuleError),
+}
+
+impl std::fmt::Display for BatchResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchResult::Solved(moves) => write!(f, "solvable in {moves} moves."),
+            BatchResult::Unsolvable => write!(f, "path not found."),
+            BatchResult::Malformed => write!(f, "Error: expected START_IMAGE and GOAL_MASK."),
+            BatchResult::Invalid(e) => write!(f, "Error: {e}"),
+        }
+    }
+}
+
+/// Solves each puzzle written as `start_hex goal_hex` on its own line, skipping blank lines.
+///
+/// A line that cannot be parsed does not stop the others from being solved.
+///
+/// Returns one result for each non-empty line, in order.
+pub fn solve_batch<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<BatchResult> {
+    lines
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(solve_line)
+        .collect()
+}
+
+/// Solves each of the rules, telling whether it is solvable and with which path.
+///
+/// With the `parallel` feature, the rules are solved in parallel. As with `solve`, a rule for a board
+/// with more than two empty spaces may take a very long time if it is unsolvable.
+///
+/// Returns one outcome for each rule, in order. The outcomes are never `SolveOutcome::TimedOut`.
+pub fn classify_batch(rules: &[Rule]) -> Vec<SolveOutcome> {
+    let classify = |rule: &Rule| match solve(rule) {
+        Some(path) => SolveOutcome::Solved(path),
+        None => SolveOutcome::Unsolvable,
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        rules.par_iter().map(classify).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        rules.iter().map(classify).collect()
+    }
+}
+
+/// Parses and solves the puzzle on one line of a batch.
+fn solve_line(line: &str) -> BatchResult {
+    let [start_image, goal_mask] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+        return BatchResult::Malformed;
+    };
+    match Rule::parse(start_image, goal_mask) {
+        Ok(rule) => match solve(&rule) {
+            Some(path) => BatchResult::Solved(path.len() - 1),
+            None => BatchResult::Unsolvable,
+        },
+        Err(e) => BatchResult::Invalid(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_solve_batch() {
+        // Arrange
+        let batch = "\
+            0x2113_2113_4556_4786_900a 0xff00_ff00_0000_0000_0000\n\
+            \n\
+            0x2113_2113_4455_6677_8009 0x0000_0000_0000_0ff0_0ff0\n\
+            0x2113_2113_4556_4786_900a\n\
+            0x2113_2113_4556_4786_9aaa 0x0000_0000_0000_0ff0_0ff0\n";
+
+        // Act
+        let results = solve_batch(batch.lines());
+
+        // Assert
+        assert_eq!(
+            results,
+            vec![
+                BatchResult::Solved(7),
+                BatchResult::Unsolvable,
+                BatchResult::Malformed,
+                BatchResult::Invalid(RuleError::StartBoardInvalidEmptyCount {
+                    found: 0,
+                    expected: 2
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_batch() {
+        // Arrange
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let rules = [
+            Rule::new(
+                &Board::new(0x2113_2113_4556_4786_900a),
+                &BitPattern::new(0xff00_ff00_0000_0000_0000),
+            ),
+            Rule::new(&Board::new(0x2113_2113_4455_6677_8009), &goal_mask),
+            Rule::new(&Board::new(0x2113_2113_4455_0000_0000), &goal_mask),
+        ];
+
+        // Act
+        let outcomes = classify_batch(&rules);
+
+        // Assert
+        assert_eq!(outcomes.len(), rules.len());
+        for (rule, outcome) in rules.iter().zip(&outcomes) {
+            if let SolveOutcome::Solved(path) = outcome {
+                assert!(verify_solution(rule, path));
+            }
+        }
+        assert!(matches!(&outcomes[0], SolveOutcome::Solved(path) if path.len() - 1 == 7));
+        assert_eq!(outcomes[1], SolveOutcome::Unsolvable);
+        assert!(matches!(outcomes[2], SolveOutcome::Solved(_)));
+    }
+}
@@ -1,3 +1,5 @@
+mod grid_pattern;
+
 #[cfg(feature = "impl_u128")]
 mod impl_u128;
 
@@ -9,3 +11,5 @@ pub use impl_u16x5::*;
 
 #[cfg(feature = "impl_u128")]
 pub use impl_u128::*;
+
+pub use grid_pattern::GridPattern;
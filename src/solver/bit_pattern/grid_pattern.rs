@@ -0,0 +1,315 @@
+use super::super::Direction;
+use super::super::Piece;
+
+/// The maximum number of columns, limited by the number of 4-bit cells in a `u64` row.
+const MAX_WIDTH: usize = 16;
+
+/// A bit pattern for boards of any size up to 16 columns, such as 6x6.
+///
+/// `GridPattern` is a `Vec`-backed counterpart of `BitPattern`, which is fixed to 4x5.
+/// Each row is stored in a `u64`, where each cell is encoded as a 4-bit value
+/// and the leftmost cell occupies the most significant nibble of the row.
+///
+/// The solver still works on 4x5 boards only, so `Board` and `Rule` are not built on `GridPattern` yet.
+#[derive(Clone, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+pub struct GridPattern {
+    width: usize,
+    rows: Vec<u64>,
+}
+
+impl GridPattern {
+    /// Creates a new `GridPattern` with the given number of columns from its rows, top to bottom.
+    ///
+    /// Returns `None` if the width is not supported or a row does not fit in the width.
+    pub fn from_rows(width: usize, rows: Vec<u64>) -> Option<Self> {
+        if width == 0 || width > MAX_WIDTH {
+            return None;
+        }
+        let pattern = Self { width, rows };
+        let row_mask = pattern.row_mask();
+        if pattern.rows.iter().any(|&row| row & !row_mask != 0) {
+            return None;
+        }
+        Some(pattern)
+    }
+
+    /// Returns the number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows.
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the rows, top to bottom.
+    pub fn rows(&self) -> &[u64] {
+        &self.rows
+    }
+
+    /// Checks if the bit pattern is empty.
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|&row| row == 0)
+    }
+
+    /// Checks if the bit pattern is not empty.
+    pub fn is_not_empty(&self) -> bool {
+        !self.is_empty()
+    }
+
+    /// Returns the mask covering all cells of a row.
+    fn row_mask(&self) -> u64 {
+        u64::MAX >> (64 - self.width * 4)
+    }
+
+    /// Returns a new `GridPattern` of the same size with each row mapped by the given function.
+    fn map_rows(&self, f: impl Fn(u64) -> u64) -> Self {
+        let row_mask = self.row_mask();
+        Self {
+            width: self.width,
+            rows: self.rows.iter().map(|&row| f(row) & row_mask).collect(),
+        }
+    }
+
+    /// Moves the bit pattern in the specified direction.
+    pub fn moved(&self, direction: Direction) -> Self {
+        let mut rows = self.rows.clone();
+        match direction {
+            Direction::Up => {
+                rows.rotate_left(1);
+                if let Some(last) = rows.last_mut() {
+                    *last = 0;
+                }
+            }
+            Direction::Down => {
+                rows.rotate_right(1);
+                if let Some(first) = rows.first_mut() {
+                    *first = 0;
+                }
+            }
+            Direction::Left => return self.map_rows(|row| row << 4),
+            Direction::Right => return self.map_rows(|row| row >> 4),
+        }
+        Self {
+            width: self.width,
+            rows,
+        }
+    }
+
+    /// Mirrors the bit pattern by reversing the cells of each row.
+    pub fn mirrored(&self) -> Self {
+        let width = self.width;
+        self.map_rows(|row| (0..width).fold(0, |acc, i| (acc << 4) | ((row >> (i * 4)) & 0xf)))
+    }
+
+    /// Returns a bit pattern representing the area occupied by the given piece.
+    pub fn mask_of(&self, piece: Piece) -> Self {
+        let repeated_id = (piece.id as u64) * 0x1111_1111_1111_1111;
+        self.map_rows(|row| {
+            let mut mask = row ^ repeated_id;
+            mask = ((mask >> 1) | mask) & 0x5555_5555_5555_5555;
+            mask = ((mask >> 2) | mask) & 0x1111_1111_1111_1111;
+            mask |= mask << 1;
+            mask |= mask << 2;
+            !mask
+        })
+    }
+
+    /// Attempts to move the specified piece in the given direction.
+    pub fn move_piece(&self, piece: Piece, direction: Direction) -> Option<Self> {
+        let piece_mask = self.mask_of(piece);
+        let moved_piece_mask = piece_mask.moved(direction);
+        if moved_piece_mask.count_cells() != piece_mask.count_cells() {
+            // The target piece is on the edge.
+            return None;
+        }
+        let other_pieces = self.zip_rows(&piece_mask, |row, mask| row & !mask)?;
+        if other_pieces
+            .zip_rows(&moved_piece_mask, |row, mask| row & mask)?
+            .is_not_empty()
+        {
+            // There is another piece in the direction of the target piece.
+            return None;
+        }
+        let moved_target_piece = self
+            .zip_rows(&piece_mask, |row, mask| row & mask)?
+            .moved(direction);
+        other_pieces.zip_rows(&moved_target_piece, |row, moved| row | moved)
+    }
+
+    /// Returns the number of non-empty cells.
+    fn count_cells(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|&row| {
+                (0..self.width)
+                    .filter(|i| (row >> (i * 4)) & 0xf != 0)
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Combines the rows of two bit patterns of the same size with the given function.
+    ///
+    /// Returns `None` if the patterns differ in width or height.
+    fn zip_rows(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Option<Self> {
+        if self.width != other.width || self.rows.len() != other.rows.len() {
+            return None;
+        }
+        Some(Self {
+            width: self.width,
+            rows: self
+                .rows
+                .iter()
+                .zip(other.rows.iter())
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+        })
+    }
+}
+
+impl std::fmt::Display for GridPattern {
+    /// Formats the `GridPattern` as a hexadecimal string with underscores between rows.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self.width;
+        self.rows.iter().try_fold("[", |sep, row| {
+            write!(f, "{sep}{row:0>width$x}")?;
+            Ok("_")
+        })?;
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern_6x6() -> GridPattern {
+        GridPattern::from_rows(
+            6,
+            vec![0x211003, 0x211003, 0x445566, 0x700008, 0x799aa8, 0xbbccdd],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn from_rows_should_validate_width() {
+        // Act & Assert
+        assert!(GridPattern::from_rows(6, vec![0x123456]).is_some());
+        assert!(GridPattern::from_rows(6, vec![0x1234567]).is_none());
+        assert!(GridPattern::from_rows(0, vec![]).is_none());
+        assert!(GridPattern::from_rows(17, vec![]).is_none());
+    }
+
+    #[test]
+    fn moved_should_shift_6x6_pattern() {
+        // Arrange
+        let bit_pattern = pattern_6x6();
+
+        // Act & Assert
+        assert_eq!(
+            bit_pattern.moved(Direction::Up).rows(),
+            &[0x211003, 0x445566, 0x700008, 0x799aa8, 0xbbccdd, 0]
+        );
+        assert_eq!(
+            bit_pattern.moved(Direction::Down).rows(),
+            &[0, 0x211003, 0x211003, 0x445566, 0x700008, 0x799aa8]
+        );
+        assert_eq!(
+            bit_pattern.moved(Direction::Left).rows(),
+            &[0x110030, 0x110030, 0x455660, 0x000080, 0x99aa80, 0xbccdd0]
+        );
+        assert_eq!(
+            bit_pattern.moved(Direction::Right).rows(),
+            &[0x021100, 0x021100, 0x044556, 0x070000, 0x0799aa, 0x0bbccd]
+        );
+    }
+
+    #[test]
+    fn mirrored_should_reverse_each_6x6_row() {
+        // Arrange
+        let bit_pattern = pattern_6x6();
+
+        // Act & Assert
+        assert_eq!(
+            bit_pattern.mirrored().rows(),
+            &[0x300112, 0x300112, 0x665544, 0x800007, 0x8aa997, 0xddccbb]
+        );
+        assert_eq!(bit_pattern.mirrored().mirrored(), bit_pattern);
+    }
+
+    #[test]
+    fn move_piece_should_move_on_6x6_board() {
+        // Arrange
+        let bit_pattern = pattern_6x6();
+
+        // Act & Assert: The large piece moves right into the empty cells
+        assert_eq!(
+            bit_pattern.move_piece(Piece::new(1), Direction::Right),
+            GridPattern::from_rows(
+                6,
+                vec![0x201103, 0x201103, 0x445566, 0x700008, 0x799aa8, 0xbbccdd],
+            )
+        );
+
+        // Act & Assert: The piece on the right edge cannot move right
+        assert_eq!(
+            bit_pattern.move_piece(Piece::new(3), Direction::Right),
+            None
+        );
+
+        // Act & Assert: Another piece is in the way
+        assert_eq!(bit_pattern.move_piece(Piece::new(1), Direction::Down), None);
+
+        // Act & Assert: A horizontal piece moves up
+        assert_eq!(
+            bit_pattern.move_piece(Piece::new(9), Direction::Up),
+            GridPattern::from_rows(
+                6,
+                vec![0x211003, 0x211003, 0x445566, 0x799008, 0x700aa8, 0xbbccdd],
+            )
+        );
+    }
+
+    #[test]
+    fn mask_of_should_return_piece_mask() {
+        // Arrange
+        let bit_pattern = pattern_6x6();
+
+        // Act & Assert
+        assert_eq!(
+            bit_pattern.mask_of(Piece::new(1)).rows(),
+            &[0x0ff000, 0x0ff000, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            bit_pattern.mask_of(Piece::new(8)).rows(),
+            &[0, 0, 0, 0x00000f, 0x00000f, 0]
+        );
+    }
+
+    #[test]
+    fn zip_rows_should_reject_size_mismatch() {
+        // Arrange
+        let bit_pattern = pattern_6x6();
+        let shorter = GridPattern::from_rows(6, vec![0x211003]).unwrap();
+        let narrower = GridPattern::from_rows(5, vec![0; 6]).unwrap();
+
+        // Act & Assert
+        assert_eq!(bit_pattern.zip_rows(&shorter, |a, b| a & b), None);
+        assert_eq!(bit_pattern.zip_rows(&narrower, |a, b| a & b), None);
+        assert_eq!(
+            bit_pattern.zip_rows(&bit_pattern, |a, b| a & b),
+            Some(bit_pattern)
+        );
+    }
+
+    #[test]
+    fn display_should_format_as_hex_string() {
+        // Act & Assert
+        assert_eq!(
+            format!("{}", pattern_6x6()),
+            "[211003_211003_445566_700008_799aa8_bbccdd]"
+        );
+    }
+}
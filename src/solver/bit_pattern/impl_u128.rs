@@ -8,6 +8,7 @@ use super::super::Piece;
 /// It is used to store the state of the board, the shape of pieces,
 /// or bitmasks for various operations in the puzzle solver.
 #[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BitPattern {
     pattern: u128,
 }
@@ -72,6 +73,21 @@ impl BitPattern {
         Self::new(pattern)
     }
 
+    /// Flips the bit pattern vertically by reversing the order of the rows.
+    pub fn flipped_vertically(&self) -> Self {
+        let pattern = (self.pattern >> 64) & 0xffff
+            | ((self.pattern >> 48) & 0xffff) << 16
+            | ((self.pattern >> 32) & 0xffff) << 32
+            | ((self.pattern >> 16) & 0xffff) << 48
+            | (self.pattern & 0xffff) << 64;
+        Self::new(pattern)
+    }
+
+    /// Rotates the bit pattern by 180 degrees.
+    pub fn rotated_180(&self) -> Self {
+        self.mirrored().flipped_vertically()
+    }
+
     /// Symmetrizes the bit pattern by swapping pairs of pieces.
     pub fn symmetrized(&self, pairs: &Vec<(Piece, Piece)>) -> BitPattern {
         let mut new_pattern = self.pattern;
@@ -89,6 +105,16 @@ impl BitPattern {
         Self::new(self.mask_of_u128(piece))
     }
 
+    /// Returns the pattern as a bare string of 20 hex digits, without brackets or separators.
+    pub fn to_hex20(&self) -> String {
+        format!("{:0>20x}", self.pattern & BIT_PATTERN_MASK)
+    }
+
+    /// Returns the number of cells occupied by the given piece.
+    pub fn count_piece_cells(&self, piece: Piece) -> usize {
+        self.mask_of_u128(piece).count_ones() as usize / 4
+    }
+
     /// Returns a bit pattern representing the area occupied by the given piece.
     fn mask_of_u128(&self, piece: Piece) -> u128 {
         let mut mask = self.pattern;
@@ -135,17 +161,14 @@ impl std::ops::Not for BitPattern {
 
 impl std::fmt::Display for BitPattern {
     /// Formats the `BitPattern` as a hexadecimal string with underscores between rows.
+    /// Each row is formatted from its own 16 bits, so no string slicing is needed.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let hex20 = format!("{:0>20x}", self.pattern & BIT_PATTERN_MASK);
-        write!(
-            f,
-            "[{}_{}_{}_{}_{}]",
-            &hex20[0..4],
-            &hex20[4..8],
-            &hex20[8..12],
-            &hex20[12..16],
-            &hex20[16..20]
-        )
+        (0..5).rev().try_fold("[", |sep, row| {
+            write!(f, "{}{:04x}", sep, (self.pattern >> (row * 16)) & 0xffff)?;
+            Ok("_")
+        })?;
+        write!(f, "]")?;
+        Ok(())
     }
 }
 
@@ -265,6 +288,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flipped_vertically_should_reverse_rows() {
+        // Arrange
+        let bit_pattern = BitPattern::new(0x2113_2113_4455_6789_6009);
+
+        // Act & Assert
+        assert_eq!(
+            bit_pattern.flipped_vertically(),
+            BitPattern::new(0x6009_6789_4455_2113_2113)
+        );
+    }
+
+    #[test]
+    fn rotated_180_should_reverse_rows_and_columns() {
+        // Arrange
+        let bit_pattern = BitPattern::new(0x2113_2113_4455_6789_6009);
+
+        // Act & Assert
+        assert_eq!(
+            bit_pattern.rotated_180(),
+            BitPattern::new(0x9006_9876_5544_3112_3112)
+        );
+        assert_eq!(bit_pattern.rotated_180().rotated_180(), bit_pattern);
+    }
+
     #[test]
     fn symmetrized_should_swap_pairs() {
         // Arrange
@@ -317,6 +365,16 @@ mod tests {
         assert_eq!(displayed, "[2113_2113_4455_6789_6009]");
     }
 
+    #[test]
+    fn to_hex20_should_format_without_brackets_or_separators() {
+        // Arrange
+        let bit_pattern = BitPattern::new(0x0113_2113_4455_6789_6009);
+        // Act
+        let hex20 = bit_pattern.to_hex20();
+        // Assert
+        assert_eq!(hex20, "01132113445567896009");
+    }
+
     #[test]
     fn moved_should_return_empty_when_all_zero() {
         // Arrange
@@ -350,4 +408,16 @@ mod tests {
             BitPattern::new(0x0000_0000_0000_0000_0000)
         );
     }
+
+    #[test]
+    fn count_piece_cells_should_count_cells_of_each_shape() {
+        // Arrange
+        let bit_pattern = BitPattern::new(0x2113_2113_4455_6789_6009);
+        // Act & Assert
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(1)), 4);
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(4)), 2);
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(6)), 2);
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(7)), 1);
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(0xa)), 0);
+    }
 }
@@ -11,6 +11,7 @@ const SIZE: usize = 5;
 /// It is used to store the state of the board, the shape of pieces,
 /// or bitmasks for various operations in the puzzle solver.
 #[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BitPattern {
     array: [u16; SIZE],
 }
@@ -99,6 +100,18 @@ impl BitPattern {
         (data << 12) & 0xf000 | (data << 4) & 0x0f00 | (data >> 4) & 0x00f0 | (data >> 12) & 0x000f
     }
 
+    /// Flips the bit pattern vertically by reversing the order of the rows.
+    pub fn flipped_vertically(&self) -> Self {
+        let mut new_array = self.array;
+        new_array.reverse();
+        Self::from_u16_array(new_array)
+    }
+
+    /// Rotates the bit pattern by 180 degrees.
+    pub fn rotated_180(&self) -> Self {
+        self.mirrored().flipped_vertically()
+    }
+
     /// Symmetrizes the bit pattern by swapping pairs of pieces.
     pub fn symmetrized(&self, pairs: &Vec<(Piece, Piece)>) -> BitPattern {
         let mut new_images = self.array;
@@ -128,6 +141,19 @@ impl BitPattern {
         Self::from_u16_array(new_array)
     }
 
+    /// Returns the pattern as a bare string of 20 hex digits, without brackets or separators.
+    pub fn to_hex20(&self) -> String {
+        self.array.iter().map(|v| format!("{v:04x}")).collect()
+    }
+
+    /// Returns the number of cells occupied by the given piece.
+    pub fn count_piece_cells(&self, piece: Piece) -> usize {
+        self.array
+            .iter()
+            .map(|&v| Self::mask_of_piece_u16(v, piece).count_ones() as usize / 4)
+            .sum()
+    }
+
     fn mask_of_piece_u16(data: u16, piece: Piece) -> u16 {
         let mut mask = data;
         mask ^= (piece.id as u16) * 0x1111;
@@ -340,6 +366,31 @@ mod tests {
         assert_eq_hex!(BitPattern::mirrored_u16(0x9abc), 0xcba9);
     }
 
+    #[test]
+    fn flipped_vertically_should_reverse_rows() {
+        // Arrange
+        let bit_pattern = BitPattern::new(0x2113_2113_4455_6789_6009);
+
+        // Act & Assert
+        assert_eq!(
+            bit_pattern.flipped_vertically(),
+            BitPattern::new(0x6009_6789_4455_2113_2113)
+        );
+    }
+
+    #[test]
+    fn rotated_180_should_reverse_rows_and_columns() {
+        // Arrange
+        let bit_pattern = BitPattern::new(0x2113_2113_4455_6789_6009);
+
+        // Act & Assert
+        assert_eq!(
+            bit_pattern.rotated_180(),
+            BitPattern::new(0x9006_9876_5544_3112_3112)
+        );
+        assert_eq!(bit_pattern.rotated_180().rotated_180(), bit_pattern);
+    }
+
     #[test]
     fn symmetrized_should_swap_pairs() {
         // Arrange
@@ -415,6 +466,16 @@ mod tests {
         assert_eq!(displayed, "[2113_2113_4455_6789_6009]");
     }
 
+    #[test]
+    fn to_hex20_should_format_without_brackets_or_separators() {
+        // Arrange
+        let bit_pattern = BitPattern::new(0x0113_2113_4455_6789_6009);
+        // Act
+        let hex20 = bit_pattern.to_hex20();
+        // Assert
+        assert_eq!(hex20, "01132113445567896009");
+    }
+
     #[test]
     fn moved_should_return_empty_when_all_zero() {
         // Arrange
@@ -448,4 +509,16 @@ mod tests {
             BitPattern::new(0x0000_0000_0000_0000_0000)
         );
     }
+
+    #[test]
+    fn count_piece_cells_should_count_cells_of_each_shape() {
+        // Arrange
+        let bit_pattern = BitPattern::new(0x2113_2113_4455_6789_6009);
+        // Act & Assert
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(1)), 4);
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(4)), 2);
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(6)), 2);
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(7)), 1);
+        assert_eq!(bit_pattern.count_piece_cells(Piece::new(0xa)), 0);
+    }
 }
@@ -0,0 +1,81 @@
+use super::BitPattern;
+use super::Board;
+use super::Piece;
+use super::Rule;
+use super::State;
+use super::solve::BoardKey;
+use super::solve::get_neighbors_with_keys;
+use std::collections::HashSet;
+
+/// Finds the fewest empty spaces with which the puzzle can be solved, by removing pieces from the board.
+///
+/// The board itself is tried first, then boards with other pieces than the large piece removed,
+/// in increasing order of the number of empty spaces.
+/// This may take a long time, since each candidate board is checked in turn.
+///
+/// Returns the number of empty spaces, or `None` if the puzzle cannot be solved even with only the large piece.
+pub fn min_blanks_to_solve(start: &Board, goal_mask: &BitPattern) -> Option<usize> {
+    let removable = start
+        .pieces()
+        .into_iter()
+        .filter(|&p| p != Piece::new(1))
+        .collect::<Vec<_>>();
+
+    // Each subset of removable pieces, as a bit set, with the board after removing them.
+    let mut candidates = (0..1usize << removable.len())
+        .map(|subset| {
+            let board = removable
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| subset & (1 << i) != 0)
+                .fold(start.clone(), |board, (_, &piece)| {
+                    Board::from_bitpattern(board.pattern & !board.pattern.mask_of(piece))
+                });
+            (board.empty_cells().len(), subset, board)
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|&(blanks, subset, _)| (blanks, subset));
+
+    candidates
+        .into_iter()
+        .find(|(_, _, board)| is_solvable(&Rule::new(board, goal_mask)))
+        .map(|(blanks, _, _)| blanks)
+}
+
+/// Checks if a goal board is reachable from the rule's starting board.
+///
+/// Unlike `solve`, every visited board is kept. The breadth-first search of `solve` only remembers
+/// the last three generations, which may not be enough to end the search for some unsolvable boards
+/// with many empty spaces.
+fn is_solvable(rule: &Rule) -> bool {
+    let mut visited = HashSet::from([BoardKey::create(rule, &rule.start)]);
+    let mut stack = vec![rule.start.clone()];
+    while let Some(board) = stack.pop() {
+        if rule.is_finished(&board) {
+            return true;
+        }
+        for (next_state, key) in get_neighbors_with_keys(rule, &State::new(&board)) {
+            if visited.insert(key) {
+                stack.push(next_state.board);
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_blanks_to_solve() {
+        // Arrange
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let solvable = Board::new(0x2113_2113_4455_0000_0000);
+        let locked = Board::new(0x2113_2113_4455_6677_8009);
+
+        // Act & Assert
+        assert_eq!(min_blanks_to_solve(&solvable, &goal_mask), Some(8));
+        assert_eq!(min_blanks_to_solve(&locked, &goal_mask), Some(4));
+    }
+}
@@ -1,12 +1,72 @@
 use super::BitPattern;
 use super::Direction;
+use super::MovePath;
 use super::Piece;
+use super::Rule;
+use super::Shape;
+use super::direction::ALL_DIRECTIONS;
+use super::shape::classify;
+use super::solve::BoardKey;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Board {
     pub pattern: BitPattern,
 }
 
+/// Defines errors that may occur when building a board.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BoardError {
+    /// A cell contains a piece ID above `0xf`.
+    InvalidPieceId(u8),
+    /// The letter diagram does not have exactly 20 cells.
+    InvalidLetterCount(usize),
+    /// The letter diagram contains a letter other than `C`, `V`, `H`, `S` and `.`.
+    UnknownLetter(char),
+    /// A piece in the letter diagram does not have the shape of its letter, at the given (row, column).
+    InvalidLetterShape(usize, usize),
+    /// The letter diagram has more pieces than can be given an ID.
+    TooManyPieces,
+    /// The letter diagram has more than one large piece, the second at the given (row, column).
+    ExtraLargePiece(usize, usize),
+    /// A token of the wide notation is not two hex digits or `..`.
+    InvalidToken(String),
+}
+
+/// Defines the reasons why a piece cannot be moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    /// The piece is on the edge of the board in the direction of the move.
+    OnEdge,
+    /// Another piece is in the way.
+    Blocked,
+    /// The piece is not on the board.
+    NoSuchPiece,
+}
+
+/// Options for rendering a board as text with `Board::render`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BoardDisplayOptions {
+    /// Whether to add column numbers (0-3) above the board and row numbers (0-4) to its left.
+    pub labels: bool,
+    /// The character shown for empty spaces.
+    pub empty: char,
+    /// The text inserted between the cells of a row.
+    pub separator: String,
+    /// The piece whose cells are shown as `#` to stand out, such as the goal piece given by `Rule::goal_piece`.
+    pub highlight: Option<Piece>,
+}
+
+impl Default for BoardDisplayOptions {
+    fn default() -> Self {
+        Self {
+            labels: false,
+            empty: '.',
+            separator: String::new(),
+            highlight: None,
+        }
+    }
+}
+
 static EDGE_TOP: BitPattern = BitPattern::new(0xffff_0000_0000_0000_0000);
 static EDGE_BOTTOM: BitPattern = BitPattern::new(0x0000_0000_0000_0000_ffff);
 static EDGE_LEFT: BitPattern = BitPattern::new(0xf000_f000_f000_f000_f000);
@@ -25,28 +85,644 @@ impl Board {
 
     /// Attempts to move the specified piece in the given direction.
     pub fn move_piece(&self, piece: Piece, direction: Direction) -> Option<Board> {
+        self.try_move_piece(piece, direction).ok()
+    }
+
+    /// Attempts to move the specified piece in the given direction, returning the reason if it cannot be moved.
+    pub fn try_move_piece(&self, piece: Piece, direction: Direction) -> Result<Board, MoveError> {
+        self.shift_piece(piece, direction, false)
+    }
+
+    /// Attempts to move the specified piece like `move_piece`, but on a toroidal board.
+    ///
+    /// The cells that would leave the board over an edge enter it from the opposite edge instead,
+    /// as long as the cells there are empty.
+    pub fn move_piece_wrapping(&self, piece: Piece, direction: Direction) -> Option<Board> {
+        self.shift_piece(piece, direction, true).ok()
+    }
+
+    /// Attempts to move the piece covered by the mask like `move_piece`, without computing the mask again.
+    ///
+    /// The mask must be `self.pattern.mask_of(piece)` for a piece on the board. The mask of the moved piece
+    /// is returned along with the board, so that a double move need not compute it either.
+    pub(crate) fn move_piece_fast(
+        &self,
+        piece_mask: &BitPattern,
+        direction: Direction,
+    ) -> Option<(Board, BitPattern)> {
+        self.shift_mask(piece_mask, direction, false).ok()
+    }
+
+    /// Attempts to move the piece covered by the mask like `move_piece_fast`, but on a toroidal board.
+    pub(crate) fn move_piece_fast_wrapping(
+        &self,
+        piece_mask: &BitPattern,
+        direction: Direction,
+    ) -> Option<(Board, BitPattern)> {
+        self.shift_mask(piece_mask, direction, true).ok()
+    }
+
+    /// Moves the piece one cell in the given direction, wrapping around the edges if `wrap` is set.
+    fn shift_piece(
+        &self,
+        piece: Piece,
+        direction: Direction,
+        wrap: bool,
+    ) -> Result<Board, MoveError> {
         let piece_mask = self.pattern.mask_of(piece);
-        let edge_mask = match direction {
-            Direction::Up => EDGE_TOP,
-            Direction::Down => EDGE_BOTTOM,
-            Direction::Left => EDGE_LEFT,
-            Direction::Right => EDGE_RIGHT,
-        };
-        if (edge_mask & piece_mask).is_not_empty() {
+        self.shift_mask(&piece_mask, direction, wrap)
+            .map(|(board, _)| board)
+    }
+
+    /// Moves the piece covered by the mask one cell in the given direction, returning the moved mask too.
+    fn shift_mask(
+        &self,
+        piece_mask: &BitPattern,
+        direction: Direction,
+        wrap: bool,
+    ) -> Result<(Board, BitPattern), MoveError> {
+        let piece_mask = *piece_mask;
+        if piece_mask.is_empty() {
+            return Err(MoveError::NoSuchPiece);
+        }
+        let edge_mask = edge_of(direction);
+        let on_edge = (edge_mask & piece_mask).is_not_empty();
+        if on_edge && !wrap {
             // The target piece is on the edge.
-            return None;
+            return Err(MoveError::OnEdge);
         }
+        let shifted = |pattern: BitPattern| {
+            if on_edge {
+                wrapped(pattern, direction, edge_mask)
+            } else {
+                pattern.moved(direction)
+            }
+        };
         let other_pieces = self.pattern & !piece_mask;
-        let moved_piece_mask = piece_mask.moved(direction);
+        let moved_piece_mask = shifted(piece_mask);
         if (other_pieces & moved_piece_mask).is_not_empty() {
             // There is another piece in the direction of the target piece.
-            return None;
+            return Err(MoveError::Blocked);
         }
 
         let target_piece = self.pattern & piece_mask;
-        let moved_target_piece = target_piece.moved(direction);
+        let moved_target_piece = shifted(target_piece);
         let next_board = Board::from_bitpattern(other_pieces | moved_target_piece);
-        Some(next_board)
+        Ok((next_board, moved_piece_mask))
+    }
+
+    /// Returns all pieces present on the board, in ascending order of ID.
+    pub fn pieces(&self) -> Vec<Piece> {
+        (0x1u8..=0xf)
+            .map(Piece::new)
+            .filter(|&p| self.pattern.mask_of(p).is_not_empty())
+            .collect()
+    }
+
+    /// Returns the pieces with a cell on any edge of the board, in ascending order of ID.
+    pub fn perimeter_pieces(&self) -> Vec<Piece> {
+        let perimeter = EDGE_TOP | EDGE_BOTTOM | EDGE_LEFT | EDGE_RIGHT;
+        self.pieces()
+            .into_iter()
+            .filter(|&piece| (self.pattern.mask_of(piece) & perimeter).is_not_empty())
+            .collect()
+    }
+
+    /// Returns the piece occupying the cell, counting rows and columns from 0 at the top left.
+    ///
+    /// Returns `None` if the cell is empty or outside the board.
+    pub fn piece_at(&self, row: usize, col: usize) -> Option<Piece> {
+        if row >= 5 || col >= 4 {
+            return None;
+        }
+        let id = self.to_grid()[row][col];
+        (id != 0).then(|| Piece::new(id))
+    }
+
+    /// Returns every legal move on the board and the resulting board.
+    ///
+    /// Unlike the solver, any piece may be moved, including the last moved one.
+    pub fn legal_moves(&self) -> Vec<(Piece, MovePath, Board)> {
+        let mut moves = vec![];
+        let movable = ALL_DIRECTIONS
+            .iter()
+            .map(|&direction| self.movable_pieces(direction))
+            .collect::<Vec<_>>();
+        for piece in self.pieces() {
+            for (&direction, movable) in ALL_DIRECTIONS.iter().zip(&movable) {
+                if !movable.contains(&piece) {
+                    continue;
+                }
+                if let Some(next_board) = self.move_piece(piece, direction) {
+                    moves.push((piece, MovePath::One(direction), next_board.clone()));
+                    for &direction2 in ALL_DIRECTIONS {
+                        if direction.reversed() == direction2 {
+                            // Do not move in the opposite direction immediately.
+                            continue;
+                        }
+                        if let Some(next2_board) = next_board.move_piece(piece, direction2) {
+                            moves.push((piece, MovePath::Two(direction, direction2), next2_board));
+                        }
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    /// Lazily yields the same moves as `legal_moves`, without allocating a `Vec`.
+    pub fn neighbors_iter(&self) -> impl Iterator<Item = (Piece, MovePath, Board)> + '_ {
+        (0x1u8..=0xf)
+            .map(Piece::new)
+            .filter(|&p| self.pattern.mask_of(p).is_not_empty())
+            .flat_map(move |piece| {
+                ALL_DIRECTIONS.iter().filter_map(move |&direction| {
+                    self.move_piece(piece, direction)
+                        .map(|next_board| (piece, direction, next_board))
+                })
+            })
+            .flat_map(|(piece, direction, next_board)| {
+                let single = (piece, MovePath::One(direction), next_board.clone());
+                let doubles = ALL_DIRECTIONS
+                    .iter()
+                    .filter(move |&&direction2| direction.reversed() != direction2)
+                    .filter_map(move |&direction2| {
+                        next_board.move_piece(piece, direction2).map(|next2_board| {
+                            (piece, MovePath::Two(direction, direction2), next2_board)
+                        })
+                    });
+                std::iter::once(single).chain(doubles)
+            })
+    }
+
+    /// Moves the piece along the path, returning `None` if the path is empty or any step is blocked.
+    pub fn apply(&self, piece: Piece, path: &MovePath) -> Option<Board> {
+        let directions = path.directions();
+        if directions.is_empty() {
+            return None;
+        }
+        directions
+            .into_iter()
+            .try_fold(self.clone(), |board, direction| {
+                board.move_piece(piece, direction)
+            })
+    }
+
+    /// Replays single-cell moves from the start board, returning the start board and each board after a move.
+    ///
+    /// Returns the index of the first illegal move as the error.
+    pub fn replay_single_moves(
+        start: &Board,
+        moves: impl IntoIterator<Item = (Piece, Direction)>,
+    ) -> Result<Vec<Board>, usize> {
+        let mut boards = vec![start.clone()];
+        for (i, (piece, direction)) in moves.into_iter().enumerate() {
+            let board = boards
+                .last()
+                .unwrap()
+                .move_piece(piece, direction)
+                .ok_or(i)?;
+            boards.push(board);
+        }
+        Ok(boards)
+    }
+
+    /// Finds the single move of one piece that turns this board into the next board.
+    ///
+    /// Returns `None` if the boards are not one legal move apart.
+    pub fn diff(&self, next: &Board) -> Option<(Piece, MovePath)> {
+        self.neighbors_iter()
+            .find(|(_, _, board)| board == next)
+            .map(|(piece, path, _)| (piece, path))
+    }
+
+    /// Returns true if the boards are equivalent under the rule's symmetries, having the same `BoardKey`.
+    ///
+    /// Unlike `==`, a board equals its mirror image, and boards differing by swapped symmetry pairs are equal.
+    pub fn sym_eq(&self, other: &Board, rule: &Rule) -> bool {
+        BoardKey::create(rule, self) == BoardKey::create(rule, other)
+    }
+
+    /// Returns the number of cells whose piece ID differs between this board and the other board.
+    pub fn distance(&self, other: &Board) -> usize {
+        let mut diff = self.pattern.get_u128() ^ other.pattern.get_u128();
+        diff |= diff >> 1;
+        diff |= diff >> 2;
+        (diff & 0x1111_1111_1111_1111_1111).count_ones() as usize
+    }
+
+    /// Creates a new `Board` from the wide notation, where each cell is a two-digit hex piece ID
+    /// separated by whitespace, and `00` or `..` is an empty space, e.g. `"02 01 01 03 ..."`.
+    ///
+    /// The notation leaves room for IDs above `0xf`, but a board can only hold IDs up to `0xf`,
+    /// since each cell is stored in 4 bits. Larger IDs are rejected with `BoardError::InvalidPieceId`.
+    pub fn from_ascii_wide(text: &str) -> Result<Board, BoardError> {
+        let tokens = text.split_whitespace().collect::<Vec<_>>();
+        if tokens.len() != 20 {
+            return Err(BoardError::InvalidLetterCount(tokens.len()));
+        }
+        let mut grid = [[0u8; 4]; 5];
+        for (cell, token) in grid.iter_mut().flatten().zip(&tokens) {
+            *cell = match *token {
+                ".." => 0,
+                _ if token.len() == 2 && token.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    u8::from_str_radix(token, 16).unwrap_or_default()
+                }
+                _ => return Err(BoardError::InvalidToken(token.to_string())),
+            };
+        }
+        Board::try_from(grid)
+    }
+
+    /// Creates a new `Board` from the common letter notation, ignoring whitespace.
+    ///
+    /// `C` is the large piece, `V` a vertical piece, `H` a horizontal piece,
+    /// `S` a small piece and `.` an empty space, e.g. `"VCCV VCCV VHHV VSSV S..S"`.
+    /// The large piece gets ID `1`, and the other pieces get IDs from `2` in scan order.
+    /// There must be at most one large piece.
+    pub fn from_letters(letters: &str) -> Result<Board, BoardError> {
+        let cells = letters
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<Vec<_>>();
+        if cells.len() != 20 {
+            return Err(BoardError::InvalidLetterCount(cells.len()));
+        }
+        let letter_at = |row: usize, col: usize| {
+            if row < 5 && col < 4 {
+                Some(cells[row * 4 + col])
+            } else {
+                None
+            }
+        };
+
+        let mut grid = [[0u8; 4]; 5];
+        let mut next_id = 2u8;
+        let mut has_large_piece = false;
+        for row in 0..5 {
+            for col in 0..4 {
+                if grid[row][col] != 0 {
+                    // Already part of a piece.
+                    continue;
+                }
+                let letter = cells[row * 4 + col];
+                let (id, piece_cells): (u8, &[(usize, usize)]) = match letter {
+                    '.' => continue,
+                    'C' => (1, &[(0, 0), (0, 1), (1, 0), (1, 1)]),
+                    'V' => (next_id, &[(0, 0), (1, 0)]),
+                    'H' => (next_id, &[(0, 0), (0, 1)]),
+                    'S' => (next_id, &[(0, 0)]),
+                    _ => return Err(BoardError::UnknownLetter(letter)),
+                };
+                if id > 0xf {
+                    return Err(BoardError::TooManyPieces);
+                }
+                if id == 1 {
+                    if has_large_piece {
+                        return Err(BoardError::ExtraLargePiece(row, col));
+                    }
+                    has_large_piece = true;
+                }
+                for &(dr, dc) in piece_cells {
+                    let (r, c) = (row + dr, col + dc);
+                    if letter_at(r, c) != Some(letter) || grid[r][c] != 0 {
+                        return Err(BoardError::InvalidLetterShape(row, col));
+                    }
+                    grid[r][c] = id;
+                }
+                if id == next_id {
+                    next_id += 1;
+                }
+            }
+        }
+        Board::try_from(grid)
+    }
+
+    /// Returns the piece ID of each cell, row by row from the top, with `0` for empty spaces.
+    pub fn to_grid(&self) -> [[u8; 4]; 5] {
+        let value = self.pattern.get_u128();
+        let mut grid = [[0; 4]; 5];
+        for (i, cell) in grid.iter_mut().flatten().enumerate() {
+            *cell = ((value >> ((19 - i) * 4)) & 0xf) as u8;
+        }
+        grid
+    }
+
+    /// Returns the pieces that can move one cell in the given direction, in ascending order of ID.
+    ///
+    /// Unlike `move_piece`, all pieces are checked at once with bitwise operations on the whole board.
+    pub(super) fn movable_pieces(&self, direction: Direction) -> Vec<Piece> {
+        let ids = self.movable_ids(direction, &self.empty_mask());
+        (0x1u8..=0xf)
+            .filter(|&id| ids & (1 << id) != 0)
+            .map(Piece::new)
+            .collect()
+    }
+
+    /// Returns the set of pieces that can move one cell in the given direction, with bit `n` set for piece #n.
+    ///
+    /// Each cell is compared with its neighbor in the direction, for all cells at once. A piece can move
+    /// if some cell of it is next to an empty cell, and none of its cells is on the edge or next to
+    /// another piece. Only the few pieces next to an empty cell are checked one by one.
+    ///
+    /// `empties` must be the mask of the empty cells, as by `empty_mask`, which the caller may have kept.
+    pub(super) fn movable_ids(&self, direction: Direction, empties: &BitPattern) -> u16 {
+        let blank = Piece::new(0);
+        let occupied = !*empties;
+        let neighbors = self.pattern.moved(direction.reversed());
+        let edge = edge_of(direction);
+        let next_to_blank = occupied & neighbors.mask_of(blank) & !edge;
+        let mut ids = piece_ids(self.pattern & next_to_blank);
+        if ids == 0 {
+            return 0;
+        }
+        let same_piece = (self.pattern ^ neighbors).mask_of(blank);
+        let blocked = (occupied & !same_piece & !neighbors.mask_of(blank)) | (occupied & edge);
+        for id in 0x1..=0xf {
+            if ids & (1 << id) != 0
+                && (self.pattern.mask_of(Piece::new(id)) & blocked).is_not_empty()
+            {
+                ids &= !(1 << id);
+            }
+        }
+        ids
+    }
+
+    /// Renders the board as a grid of piece IDs, one row per line, according to the options.
+    pub fn render(&self, options: &BoardDisplayOptions) -> String {
+        let join = |cells: Vec<String>| cells.join(&options.separator);
+        let mut lines = vec![];
+        if options.labels {
+            let header = join((0..4).map(|col| col.to_string()).collect());
+            lines.push(format!("  {header}"));
+        }
+        for (row, ids) in self.to_grid().iter().enumerate() {
+            let cells = ids
+                .iter()
+                .map(|&id| match id {
+                    0 => options.empty.to_string(),
+                    _ if options.highlight == Some(Piece::new(id)) => "#".to_string(),
+                    _ => format!("{id:x}"),
+                })
+                .collect();
+            let line = join(cells);
+            if options.labels {
+                lines.push(format!("{row} {line}"));
+            } else {
+                lines.push(line);
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Renders the board like `Display`, with `*` in place of the cells covered by the mask.
+    ///
+    /// Useful to see which pieces lie under a goal mask or a piece mask.
+    pub fn render_with_overlay(&self, mask: &BitPattern) -> String {
+        let covered = Board::from_bitpattern(*mask).to_grid();
+        self.to_grid()
+            .iter()
+            .zip(&covered)
+            .map(|(ids, covered)| {
+                ids.iter()
+                    .zip(covered)
+                    .map(|(&id, &covered)| match (covered, id) {
+                        (1.., _) => '*',
+                        (_, 0) => '.',
+                        _ => char::from_digit(id as u32, 16).unwrap_or('?'),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders the board as an SVG image, drawing each piece as a rounded rectangle labeled with its ID.
+    ///
+    /// Each cell is `cell_px` pixels square. The color of a piece depends only on its ID.
+    pub fn to_svg(&self, cell_px: u32) -> String {
+        let grid = self.to_grid();
+        let inset = cell_px / 16;
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+            4 * cell_px,
+            5 * cell_px
+        );
+        for piece in self.pieces() {
+            let cells = (0..20u32)
+                .filter(|&i| grid[i as usize / 4][i as usize % 4] == piece.id)
+                .map(|i| (i / 4, i % 4))
+                .collect::<Vec<_>>();
+            let top = cells.iter().map(|&(row, _)| row).min().unwrap_or(0);
+            let bottom = cells.iter().map(|&(row, _)| row).max().unwrap_or(0);
+            let left = cells.iter().map(|&(_, col)| col).min().unwrap_or(0);
+            let right = cells.iter().map(|&(_, col)| col).max().unwrap_or(0);
+            let (x, y) = (left * cell_px, top * cell_px);
+            let (width, height) = ((right - left + 1) * cell_px, (bottom - top + 1) * cell_px);
+            svg += &format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" stroke="black"/>"#,
+                x + inset,
+                y + inset,
+                width - 2 * inset,
+                height - 2 * inset,
+                cell_px / 8,
+                piece_color(piece)
+            );
+            svg += &format!(
+                r#"<text x="{}" y="{}" font-size="{}" text-anchor="middle" dominant-baseline="central">{piece}</text>"#,
+                x + width / 2,
+                y + height / 2,
+                cell_px / 2
+            );
+        }
+        svg += "</svg>";
+        svg
+    }
+
+    /// Renders the board as raw RGBA pixels, row by row from the top, with 4 bytes per pixel.
+    ///
+    /// Each cell is `cell_px` pixels square, so the image is `4 * cell_px` wide and `5 * cell_px` high.
+    /// Pieces are filled with the colors of `to_svg`, inset by the same margin, on a white background.
+    pub fn to_rgba(&self, cell_px: u32) -> Vec<u8> {
+        let grid = self.to_grid();
+        let inset = cell_px / 16;
+        let id_at = |row: u32, col: u32| match grid.get(row as usize) {
+            Some(cells) => cells.get(col as usize).copied(),
+            None => None,
+        };
+        let (width, height) = (4 * cell_px, 5 * cell_px);
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (row, col) = (y / cell_px, x / cell_px);
+                let (dy, dx) = (y % cell_px, x % cell_px);
+                let id = grid[row as usize][col as usize];
+                // The margin is only on the sides facing other pieces, so the cells of a piece are joined.
+                let is_margin = (dy < inset && (row == 0 || id_at(row - 1, col) != Some(id)))
+                    || (dy >= cell_px - inset && id_at(row + 1, col) != Some(id))
+                    || (dx < inset && (col == 0 || id_at(row, col - 1) != Some(id)))
+                    || (dx >= cell_px - inset && id_at(row, col + 1) != Some(id));
+                let [r, g, b] = match id {
+                    0 => BACKGROUND_RGB,
+                    _ if is_margin => BACKGROUND_RGB,
+                    _ => piece_rgb(Piece::new(id)),
+                };
+                pixels.extend([r, g, b, 0xff]);
+            }
+        }
+        pixels
+    }
+
+    /// Returns the (row, column) of each empty cell, from the top-left to the bottom-right.
+    pub fn empty_cells(&self) -> Vec<(usize, usize)> {
+        let value = self.pattern.get_u128();
+        (0..20)
+            .filter(|&i| (value >> ((19 - i) * 4)) & 0xf == 0)
+            .map(|i| (i / 4, i % 4))
+            .collect()
+    }
+
+    /// Returns the mask of the empty cells, with all four bits of each empty cell set.
+    pub fn empty_mask(&self) -> BitPattern {
+        self.pattern.mask_of(Piece::new(0))
+    }
+
+    /// Checks that the board is internally consistent.
+    ///
+    /// The board is valid if it fits in the 20 cells, piece #1 is the 2x2 large piece, and every other
+    /// piece is small, horizontal or vertical. A piece two cells wide must not wrap from the right edge
+    /// of one row to the left edge of the next.
+    pub fn is_valid(&self) -> bool {
+        if self.pattern.get_u128() > 0xffff_ffff_ffff_ffff_ffff {
+            return false;
+        }
+        if classify(self, Piece::new(1)) == Shape::Unused {
+            return false;
+        }
+        self.pieces()
+            .into_iter()
+            .all(|piece| self.has_valid_shape(piece))
+    }
+
+    /// Checks that the piece is absent or has an allowed shape, the large one for piece #1,
+    /// without wrapping from one row to the next.
+    pub(super) fn has_valid_shape(&self, piece: Piece) -> bool {
+        let shape = classify(self, piece);
+        let mask = self.pattern.mask_of(piece);
+        let wrapped = (mask & EDGE_LEFT).is_not_empty() && (mask & EDGE_RIGHT).is_not_empty();
+        let allowed = match piece.id {
+            1 => shape == Shape::Large,
+            _ => shape.is_regular(),
+        };
+        allowed && !wrapped
+    }
+
+    /// Returns the number of connected regions of empty cells (4-connectivity).
+    pub fn empty_regions(&self) -> usize {
+        let mut unvisited = self.empty_cells();
+        let mut regions = 0;
+        while let Some(seed) = unvisited.pop() {
+            regions += 1;
+            let mut stack = vec![seed];
+            while let Some((row, col)) = stack.pop() {
+                // Collect the unvisited cells adjacent to the current cell.
+                let (adjacent, rest): (Vec<_>, Vec<_>) = unvisited
+                    .into_iter()
+                    .partition(|&(r, c)| row.abs_diff(r) + col.abs_diff(c) == 1);
+                unvisited = rest;
+                stack.extend(adjacent);
+            }
+        }
+        regions
+    }
+}
+
+/// Moves the pattern one cell in the given direction, carrying the cells on the edge over to the opposite edge.
+fn wrapped(pattern: BitPattern, direction: Direction, edge_mask: BitPattern) -> BitPattern {
+    let across = match direction {
+        Direction::Up | Direction::Down => 4,
+        Direction::Left | Direction::Right => 3,
+    };
+    let carried = (0..across).fold(pattern & edge_mask, |carried, _| {
+        carried.moved(direction.reversed())
+    });
+    (pattern & !edge_mask).moved(direction) | carried
+}
+
+impl TryFrom<[[u8; 4]; 5]> for Board {
+    type Error = BoardError;
+
+    /// Creates a `Board` from the piece ID of each cell, row by row from the top.
+    fn try_from(grid: [[u8; 4]; 5]) -> Result<Self, Self::Error> {
+        let mut image = 0u128;
+        for &id in grid.iter().flatten() {
+            if id > 0xf {
+                return Err(BoardError::InvalidPieceId(id));
+            }
+            image = (image << 4) | id as u128;
+        }
+        Ok(Board::new(image))
+    }
+}
+
+/// Returns the cells along the edge of the board in the given direction.
+fn edge_of(direction: Direction) -> BitPattern {
+    match direction {
+        Direction::Up => EDGE_TOP,
+        Direction::Down => EDGE_BOTTOM,
+        Direction::Left => EDGE_LEFT,
+        Direction::Right => EDGE_RIGHT,
+    }
+}
+
+/// Returns the set of the IDs in the bit pattern, with bit `n` set for ID `n`.
+fn piece_ids(pattern: BitPattern) -> u16 {
+    let mut value = pattern.get_u128();
+    let mut ids = 0;
+    while value != 0 {
+        let shift = value.trailing_zeros() / 4 * 4;
+        ids |= 1 << ((value >> shift) & 0xf);
+        value &= !(0xf << shift);
+    }
+    ids
+}
+
+/// Returns the fill color of the piece in `Board::to_svg`, spreading the hues of the IDs around the color wheel.
+fn piece_color(piece: Piece) -> String {
+    let hue = piece_hue(piece);
+    format!("hsl({hue}, 70%, 75%)")
+}
+
+/// Returns the hue of the piece's color in degrees.
+fn piece_hue(piece: Piece) -> u32 {
+    piece.id as u32 * 67 % 360
+}
+
+/// The color of empty cells and the margins around pieces in `Board::to_rgba`.
+const BACKGROUND_RGB: [u8; 3] = [0xff, 0xff, 0xff];
+
+/// Returns the fill color of the piece in `Board::to_rgba`, the same color as `piece_color` in RGB.
+fn piece_rgb(piece: Piece) -> [u8; 3] {
+    let (saturation, lightness) = (0.7, 0.75);
+    let hue = piece_hue(piece) as f64 / 60.0;
+    let chroma = (1.0 - (2.0 * lightness - 1.0f64).abs()) * saturation;
+    let second = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, second, 0.0),
+        1 => (second, chroma, 0.0),
+        2 => (0.0, chroma, second),
+        3 => (0.0, second, chroma),
+        4 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let lightest = lightness - chroma / 2.0;
+    [r, g, b].map(|c| ((c + lightest) * 255.0).round() as u8)
+}
+
+impl std::fmt::Display for Board {
+    /// Formats the `Board` as a grid of piece IDs, one row per line, with `.` for empty spaces.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(&BoardDisplayOptions::default()))
     }
 }
 
@@ -92,4 +768,634 @@ mod tests {
         let expected_board2 = Board::new(0x2113_2113_4455_6790_6890);
         assert_eq!(moved_result2, Some(expected_board2));
     }
+
+    #[test]
+    fn test_display() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4455_6789_6009);
+        // Act
+        let displayed = format!("{board}");
+        // Assert
+        assert_eq!(displayed, "2113\n2113\n4455\n6789\n6..9");
+    }
+
+    #[test]
+    fn test_move_piece_fast() {
+        // Arrange
+        let boards = [
+            Board::new(0x2113_2113_4556_4786_900a),
+            Board::new(0x2113_2113_4455_0000_0000),
+            Board::new(0x2003_2113_4116_4556_7789),
+        ];
+
+        for board in &boards {
+            for id in 0x1..=0xf {
+                let piece = Piece::new(id);
+                let mask = board.pattern.mask_of(piece);
+                if mask.is_empty() {
+                    continue;
+                }
+                for &direction in ALL_DIRECTIONS {
+                    // Act
+                    let fast = board.move_piece_fast(&mask, direction);
+                    let wrapping = board.move_piece_fast_wrapping(&mask, direction);
+
+                    // Assert
+                    assert_eq!(
+                        fast.as_ref().map(|(next, _)| next),
+                        board.move_piece(piece, direction).as_ref()
+                    );
+                    assert_eq!(
+                        wrapping.as_ref().map(|(next, _)| next),
+                        board.move_piece_wrapping(piece, direction).as_ref()
+                    );
+                    for (next, next_mask) in fast.iter().chain(&wrapping) {
+                        assert_eq!(*next_mask, next.pattern.mask_of(piece));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_perimeter_pieces() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act
+        let pieces = board.perimeter_pieces();
+
+        // Assert: Only pieces #5, #7 and #8 are inside
+        let ids = pieces.iter().map(|piece| piece.id).collect::<Vec<_>>();
+        assert_eq!(ids, [0x1, 0x2, 0x3, 0x4, 0x6, 0x9, 0xa]);
+    }
+
+    #[test]
+    fn test_piece_at() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act & Assert: Occupied cells
+        assert_eq!(board.piece_at(0, 0), Some(Piece::new(2)));
+        assert_eq!(board.piece_at(1, 2), Some(Piece::new(1)));
+        assert_eq!(board.piece_at(4, 3), Some(Piece::new(0xa)));
+
+        // Act & Assert: Empty cells
+        assert_eq!(board.piece_at(4, 1), None);
+        assert_eq!(board.piece_at(4, 2), None);
+
+        // Act & Assert: Out of range
+        assert_eq!(board.piece_at(5, 0), None);
+        assert_eq!(board.piece_at(0, 4), None);
+        assert_eq!(board.piece_at(usize::MAX, usize::MAX), None);
+    }
+
+    #[test]
+    fn test_movable_pieces() {
+        // Arrange
+        let mut boards = vec![
+            Board::new(0x2113_2113_4556_4786_900a),
+            Board::new(0x2113_2113_4455_0000_0000),
+            Board::new(0x1100_1100_0000_0000_0000),
+            Board::new(0x2113_2113_4556_4786_9abc),
+        ];
+        boards.extend(
+            crate::random_walk(&boards[0], 200, 1)
+                .into_iter()
+                .map(|state| state.board),
+        );
+
+        for board in &boards {
+            for &direction in ALL_DIRECTIONS {
+                // Act
+                let movable = board.movable_pieces(direction);
+
+                // Assert
+                let expected = board
+                    .pieces()
+                    .into_iter()
+                    .filter(|&piece| board.move_piece(piece, direction).is_some())
+                    .collect::<Vec<_>>();
+                assert_eq!(movable, expected, "{board:?} {direction}");
+            }
+        }
+        assert_eq!(
+            boards[0].movable_pieces(Direction::Down),
+            vec![Piece::new(7), Piece::new(8)]
+        );
+    }
+
+    #[test]
+    fn test_render_with_highlight() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        let options = BoardDisplayOptions {
+            highlight: Some(Piece::new(1)),
+            ..BoardDisplayOptions::default()
+        };
+
+        // Act
+        let rendered = board.render(&options);
+
+        // Assert
+        assert_eq!(rendered, "2##3\n2##3\n4556\n4786\n9..a");
+    }
+
+    #[test]
+    fn test_render_with_overlay() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+
+        // Act
+        let rendered = board.render_with_overlay(&goal_mask);
+
+        // Assert
+        assert_eq!(rendered, "2113\n2113\n4556\n4**6\n9**a");
+        assert_eq!(
+            board.render_with_overlay(&BitPattern::new(0)),
+            format!("{board}")
+        );
+    }
+
+    #[test]
+    fn test_to_svg() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        let moved = Board::new(0x2113_2113_4556_4086_970a);
+
+        // Act
+        let svg = board.to_svg(40);
+
+        // Assert
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="160" height="200">"#)
+        );
+        assert!(svg.ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect").count(), 10);
+        assert_eq!(svg.matches("<text").count(), 10);
+        assert!(svg.contains(r#"<rect x="42" y="2" width="76" height="76" rx="5""#));
+        assert_eq!(svg, board.to_svg(40));
+        assert!(moved.to_svg(40).contains(&piece_color(Piece::new(7))));
+    }
+
+    #[test]
+    fn test_sym_eq() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let mirrored = Board::new(0x3112_3112_6554_6874_a009);
+        let moved = Board::new(0x2113_2113_4556_4086_970a);
+
+        // Act & Assert
+        assert!(rule.start.sym_eq(&mirrored, &rule));
+        assert_ne!(rule.start, mirrored);
+        assert!(rule.start.sym_eq(&rule.start, &rule));
+        assert!(!rule.start.sym_eq(&moved, &rule));
+    }
+
+    #[test]
+    fn test_to_rgba() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        let moved = Board::new(0x2113_2113_4556_4086_970a);
+        let pixel = |pixels: &[u8], x: usize, y: usize| {
+            let i = (y * 4 * 40 + x) * 4;
+            pixels[i..i + 4].to_vec()
+        };
+
+        // Act
+        let pixels = board.to_rgba(40);
+        let moved_pixels = moved.to_rgba(40);
+
+        // Assert
+        assert_eq!(pixels.len(), 160 * 200 * 4);
+        assert_eq!(pixels, board.to_rgba(40));
+        // Piece #7 keeps its color after moving down a cell
+        assert_eq!(pixel(&pixels, 60, 140), [0xa3, 0xec, 0x93, 0xff]);
+        assert_eq!(pixel(&moved_pixels, 60, 180), pixel(&pixels, 60, 140));
+        // The large piece is one block, with a margin only around it
+        assert_eq!(
+            pixel(&pixels, 80, 40),
+            [piece_rgb(Piece::new(1)).as_slice(), &[0xff]].concat()
+        );
+        assert_eq!(pixel(&pixels, 41, 40), [0xff; 4]);
+        // Empty cells are white
+        assert_eq!(pixel(&pixels, 60, 180), [0xff; 4]);
+    }
+
+    #[test]
+    fn test_render() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4455_6789_6009);
+        let labeled = BoardDisplayOptions {
+            labels: true,
+            empty: ' ',
+            separator: " ".to_string(),
+            highlight: None,
+        };
+
+        // Act & Assert: Unlabeled
+        assert_eq!(
+            board.render(&BoardDisplayOptions::default()),
+            format!("{board}")
+        );
+
+        // Act & Assert: Labeled
+        assert_eq!(
+            board.render(&labeled),
+            "  0 1 2 3\n0 2 1 1 3\n1 2 1 1 3\n2 4 4 5 5\n3 6 7 8 9\n4 6     9"
+        );
+    }
+
+    #[test]
+    fn test_empty_cells() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4455_6789_6009);
+        // Act & Assert
+        assert_eq!(board.empty_cells(), vec![(4, 1), (4, 2)]);
+    }
+
+    #[test]
+    fn test_empty_regions() {
+        // Arrange & Act & Assert: Two adjacent empty cells
+        assert_eq!(Board::new(0x2113_2113_4556_4786_900a).empty_regions(), 1);
+
+        // Arrange & Act & Assert: Two separated empty cells
+        assert_eq!(Board::new(0x2113_2113_4056_7896_70aa).empty_regions(), 2);
+
+        // Arrange & Act & Assert: All empty
+        assert_eq!(Board::new(0x0000_0000_0000_0000_0000).empty_regions(), 1);
+
+        // Arrange & Act & Assert: No empty cells
+        assert_eq!(Board::new(0x1111_1111_1111_1111_1111).empty_regions(), 0);
+    }
+
+    #[test]
+    fn test_pieces() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4455_0000_0000);
+        // Act & Assert
+        assert_eq!(
+            board.pieces(),
+            vec![
+                Piece::new(1),
+                Piece::new(2),
+                Piece::new(3),
+                Piece::new(4),
+                Piece::new(5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        // Act
+        let moves = board.legal_moves();
+        // Assert
+        assert_eq!(moves.len(), 8);
+        assert_eq!(
+            moves[0],
+            (
+                Piece::new(7),
+                MovePath::One(Direction::Down),
+                Board::new(0x2113_2113_4556_4086_970a)
+            )
+        );
+    }
+
+    #[test]
+    fn test_neighbors_iter_matches_legal_moves() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        // Act
+        let moves = board.neighbors_iter().collect::<Vec<_>>();
+        // Assert
+        assert_eq!(moves, board.legal_moves());
+
+        // Arrange: More empty spaces
+        let board = Board::new(0x2113_2113_4055_0000_0000);
+        // Act
+        let moves = board.neighbors_iter().collect::<Vec<_>>();
+        // Assert
+        assert_eq!(moves, board.legal_moves());
+    }
+
+    #[test]
+    fn test_try_move_piece() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act & Assert
+        assert_eq!(
+            board.try_move_piece(Piece::new(7), Direction::Down),
+            Ok(Board::new(0x2113_2113_4556_4086_970a))
+        );
+        assert_eq!(
+            board.try_move_piece(Piece::new(9), Direction::Down),
+            Err(MoveError::OnEdge)
+        );
+        assert_eq!(
+            board.try_move_piece(Piece::new(1), Direction::Down),
+            Err(MoveError::Blocked)
+        );
+        assert_eq!(
+            board.try_move_piece(Piece::new(0xb), Direction::Down),
+            Err(MoveError::NoSuchPiece)
+        );
+        assert_eq!(board.move_piece(Piece::new(1), Direction::Down), None);
+    }
+
+    #[test]
+    fn test_apply() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act & Assert
+        assert_eq!(
+            board.apply(
+                Piece::new(9),
+                &MovePath::Two(Direction::Right, Direction::Right)
+            ),
+            Some(Board::new(0x2113_2113_4556_4786_009a))
+        );
+        assert_eq!(
+            board.apply(Piece::new(9), &MovePath::One(Direction::Up)),
+            None
+        );
+        assert_eq!(board.apply(Piece::new(9), &MovePath::None), None);
+    }
+
+    #[test]
+    fn test_move_piece_wrapping() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        let split = Board::new(0x2113_0113_4556_4786_290a);
+
+        // Act & Assert
+        assert_eq!(
+            board.move_piece_wrapping(Piece::new(7), Direction::Down),
+            Some(Board::new(0x2113_2113_4556_4086_970a))
+        );
+        assert_eq!(
+            board.move_piece_wrapping(Piece::new(1), Direction::Up),
+            Some(Board::new(0x2113_2003_4556_4786_911a))
+        );
+        assert_eq!(
+            Board::new(0x2113_2113_4556_4786_9a00)
+                .move_piece_wrapping(Piece::new(9), Direction::Left),
+            Some(Board::new(0x2113_2113_4556_4786_0a09))
+        );
+        assert_eq!(
+            Board::new(0x2113_2113_4556_4786_090a)
+                .move_piece_wrapping(Piece::new(2), Direction::Up),
+            Some(split.clone())
+        );
+        assert_eq!(
+            split.move_piece_wrapping(Piece::new(2), Direction::Down),
+            Some(Board::new(0x2113_2113_4556_4786_090a))
+        );
+    }
+
+    #[test]
+    fn test_move_piece_wrapping_blocked() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act & Assert
+        assert_eq!(
+            board.move_piece_wrapping(Piece::new(9), Direction::Down),
+            None
+        );
+        assert_eq!(
+            board.move_piece_wrapping(Piece::new(3), Direction::Up),
+            None
+        );
+        assert_eq!(board.move_piece(Piece::new(1), Direction::Up), None);
+    }
+
+    #[test]
+    fn test_replay_single_moves() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let moves = [
+            (Piece::new(9), Direction::Right),
+            (Piece::new(9), Direction::Right),
+            (Piece::new(7), Direction::Down),
+        ];
+
+        // Act
+        let boards = Board::replay_single_moves(&start, moves).unwrap();
+
+        // Assert
+        assert_eq!(
+            boards,
+            vec![
+                start,
+                Board::new(0x2113_2113_4556_4786_090a),
+                Board::new(0x2113_2113_4556_4786_009a),
+                Board::new(0x2113_2113_4556_4086_079a),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_single_moves_illegal() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let moves = [
+            (Piece::new(9), Direction::Right),
+            (Piece::new(9), Direction::Up),
+            (Piece::new(8), Direction::Down),
+        ];
+
+        // Act & Assert
+        assert_eq!(Board::replay_single_moves(&start, moves), Err(1));
+        assert_eq!(Board::replay_single_moves(&start, []), Ok(vec![start]));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        // Act & Assert
+        assert!(Board::new(0x2113_2113_4556_4786_900a).is_valid());
+        assert!(Board::new(0x1100_1100_0000_0000_0000).is_valid());
+    }
+
+    #[test]
+    fn test_is_valid_for_corrupt_boards() {
+        // Act & Assert: No large piece
+        assert!(!Board::new(0x2003_2003_4556_4786_900a).is_valid());
+        // An L-shaped piece
+        assert!(!Board::new(0x2113_2113_4556_4776_970a).is_valid());
+        // A horizontal piece wrapping from one row to the next
+        assert!(!Board::new(0x2113_2113_0005_5000_0000).is_valid());
+        // A large piece wrapping from one row to the next
+        assert!(!Board::new(0x0001_1001_1000_0000_0000).is_valid());
+        // More than 20 cells, which only the u128 bit pattern can hold
+        #[cfg(feature = "impl_u128")]
+        assert!(!Board::new(0xa_2113_2113_4556_4786_900a).is_valid());
+    }
+
+    #[test]
+    fn test_distance() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        let moved = Board::new(0x2113_2113_4556_4086_970a);
+
+        // Act & Assert
+        assert_eq!(board.distance(&board), 0);
+        assert_eq!(board.distance(&moved), 2);
+        assert_eq!(moved.distance(&board), 2);
+        assert_eq!(board.distance(&Board::new(0)), 18);
+    }
+
+    #[test]
+    fn test_diff() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act & Assert: One-cell move
+        assert_eq!(
+            board.diff(&Board::new(0x2113_2113_4556_4086_970a)),
+            Some((Piece::new(7), MovePath::One(Direction::Down)))
+        );
+
+        // Act & Assert: Two-cell move
+        assert_eq!(
+            board.diff(&Board::new(0x2113_2113_4556_4786_009a)),
+            Some((
+                Piece::new(9),
+                MovePath::Two(Direction::Right, Direction::Right)
+            ))
+        );
+
+        // Act & Assert: Two pieces moved
+        assert_eq!(board.diff(&Board::new(0x2113_2113_4556_4006_978a)), None);
+
+        // Act & Assert: Same board
+        assert_eq!(board.diff(&board), None);
+    }
+
+    #[test]
+    fn test_to_grid() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4455_6789_6009);
+        // Act & Assert
+        assert_eq!(
+            board.to_grid(),
+            [
+                [2, 1, 1, 3],
+                [2, 1, 1, 3],
+                [4, 4, 5, 5],
+                [6, 7, 8, 9],
+                [6, 0, 0, 9],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_try_from_grid() {
+        // Arrange & Act: Round trip
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        let round_trip = Board::try_from(board.to_grid());
+        // Assert
+        assert_eq!(round_trip, Ok(board));
+
+        // Arrange & Act: Invalid piece ID
+        let mut grid = [[0; 4]; 5];
+        grid[2][1] = 0x10;
+        // Assert
+        assert_eq!(Board::try_from(grid), Err(BoardError::InvalidPieceId(0x10)));
+    }
+
+    #[test]
+    fn test_from_letters() {
+        // Arrange: The classic Hua Rong Dao layout
+        let letters = "
+            VCCV
+            VCCV
+            VHHV
+            VSSV
+            S..S
+        ";
+        // Act & Assert
+        assert_eq!(
+            Board::from_letters(letters),
+            Ok(Board::new(0x2113_2113_4556_4786_900a))
+        );
+
+        // Arrange & Act & Assert: Single line
+        assert_eq!(
+            Board::from_letters("VCCV VCCV VHHV VSSV S..S"),
+            Ok(Board::new(0x2113_2113_4556_4786_900a))
+        );
+    }
+
+    #[test]
+    fn test_from_ascii_wide() {
+        // Arrange
+        let text = "
+            02 01 01 03
+            02 01 01 03
+            04 05 05 06
+            04 07 08 06
+            09 .. 00 0a
+        ";
+        let twenty_small_pieces = (1..=20).map(|id| format!("{id:02x}")).collect::<Vec<_>>();
+
+        // Act & Assert
+        assert_eq!(
+            Board::from_ascii_wide(text),
+            Ok(Board::new(0x2113_2113_4556_4786_900a))
+        );
+        assert_eq!(
+            Board::from_ascii_wide(&twenty_small_pieces.join(" ")),
+            Err(BoardError::InvalidPieceId(0x10))
+        );
+        assert_eq!(
+            Board::from_ascii_wide("02 01 01 03"),
+            Err(BoardError::InvalidLetterCount(4))
+        );
+        assert_eq!(
+            Board::from_ascii_wide(&text.replace("0a", "a")),
+            Err(BoardError::InvalidToken("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_from_letters_invalid() {
+        // Act & Assert: Too few cells
+        assert_eq!(
+            Board::from_letters("VCCV VCCV VHHV VSSV S."),
+            Err(BoardError::InvalidLetterCount(18))
+        );
+
+        // Act & Assert: Unknown letter
+        assert_eq!(
+            Board::from_letters("VCCV VCCV VHHV VSXV S..S"),
+            Err(BoardError::UnknownLetter('X'))
+        );
+
+        // Act & Assert: Vertical piece cut off at the bottom
+        assert_eq!(
+            Board::from_letters("VCCV VCCV SHHV SSSV S..V"),
+            Err(BoardError::InvalidLetterShape(4, 3))
+        );
+
+        // Act & Assert: Horizontal piece with an odd number of cells
+        assert_eq!(
+            Board::from_letters("VCCV VCCV HHHV VSSV S..S"),
+            Err(BoardError::InvalidLetterShape(2, 2))
+        );
+
+        // Act & Assert: Two large pieces
+        assert_eq!(
+            Board::from_letters("VCCV VCCV SCCS SCCS S..S"),
+            Err(BoardError::ExtraLargePiece(2, 1))
+        );
+    }
 }
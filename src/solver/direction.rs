@@ -1,11 +1,21 @@
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+    // The discriminants are fixed, since they index arrays of one value per direction.
+    Up = 0,
+    Down = 1,
+    Left = 2,
+    Right = 3,
 }
 
+/// All possible directions for moving pieces in the puzzle.
+pub static ALL_DIRECTIONS: &[Direction] = &[
+    Direction::Up,
+    Direction::Down,
+    Direction::Left,
+    Direction::Right,
+];
+
 impl Direction {
     /// Returns the opposite direction.
     pub fn reversed(&self) -> Direction {
@@ -34,6 +44,14 @@ impl std::fmt::Display for Direction {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_all_directions_in_discriminant_order() {
+        // Act & Assert
+        for (i, &direction) in ALL_DIRECTIONS.iter().enumerate() {
+            assert_eq!(direction as usize, i);
+        }
+    }
+
     #[test]
     fn test_direction_reversed() {
         // Act & Assert
@@ -0,0 +1,162 @@
+use super::Board;
+use super::Direction;
+use super::MovePath;
+use super::Piece;
+use super::State;
+use super::json::hex_string;
+use super::rule::parse_20_hex_digits;
+use std::io::Write;
+
+/// Defines the errors that may occur while reading a game log, with the 1-based line number.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LogError {
+    /// The log has no `start` line.
+    MissingStart,
+    /// The line is not in the `piece direction(s) -> board` format.
+    InvalidLine(usize),
+    /// The move on the line does not turn the previous board into the board on the line.
+    IllegalMove(usize),
+}
+
+/// Writes the solution path as a game log that `parse_log` can read back.
+///
+/// The first line is `start -> ` and the starting board, then each line is the moved piece,
+/// its directions and the board after the move, e.g. `7 Down -> 0x2113_2113_4556_4086_970a`.
+pub fn run_log<W: Write>(output: &mut W, path: &[State]) -> std::io::Result<()> {
+    for state in path {
+        match state.piece {
+            Some(piece) => {
+                let directions = state
+                    .path
+                    .directions()
+                    .iter()
+                    .map(|direction| direction.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                write!(output, "{piece} {directions}")?;
+            }
+            None => write!(output, "start")?,
+        }
+        writeln!(output, " -> {}", hex_string(&state.board.pattern))?;
+    }
+    Ok(())
+}
+
+/// Reads a game log written by `run_log`, checking that every move is legal.
+///
+/// Returns each move with the board after it, excluding the starting board. Blank lines are ignored.
+pub fn parse_log(log: &str) -> Result<Vec<(Piece, MovePath, Board)>, LogError> {
+    let mut lines = log
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let (line_number, line) = lines.next().ok_or(LogError::MissingStart)?;
+    let mut board = match line.split_once(" -> ") {
+        Some(("start", image)) => parse_board(image).ok_or(LogError::InvalidLine(line_number))?,
+        _ => return Err(LogError::MissingStart),
+    };
+
+    let mut moves = vec![];
+    for (line_number, line) in lines {
+        let (piece, path, next_board) =
+            parse_move(line).ok_or(LogError::InvalidLine(line_number))?;
+        if board.apply(piece, &path).as_ref() != Some(&next_board) {
+            return Err(LogError::IllegalMove(line_number));
+        }
+        board = next_board.clone();
+        moves.push((piece, path, next_board));
+    }
+    Ok(moves)
+}
+
+/// Parses a `piece direction(s) -> board` line.
+fn parse_move(line: &str) -> Option<(Piece, MovePath, Board)> {
+    let (step, image) = line.split_once(" -> ")?;
+    let mut words = step.split_whitespace();
+    let piece = Piece::try_new(u8::from_str_radix(words.next()?, 16).ok()?)?;
+    let directions = words.map(parse_direction).collect::<Option<Vec<_>>>()?;
+    let path = match directions[..] {
+        [d] => MovePath::One(d),
+        [d1, d2] => MovePath::Two(d1, d2),
+        _ => return None,
+    };
+    Some((piece, path, parse_board(image)?))
+}
+
+/// Parses a direction written by its `Display` implementation.
+fn parse_direction(word: &str) -> Option<Direction> {
+    match word {
+        "Up" => Some(Direction::Up),
+        "Down" => Some(Direction::Down),
+        "Left" => Some(Direction::Left),
+        "Right" => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+/// Parses a board written as 20 hex digits.
+fn parse_board(image: &str) -> Option<Board> {
+    parse_20_hex_digits(image).map(Board::from_bitpattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_run_log_and_parse_log() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let path = solve(&rule).unwrap();
+        let mut output = vec![];
+
+        // Act
+        run_log(&mut output, &path).unwrap();
+        let log = String::from_utf8(output).unwrap();
+        let moves = parse_log(&log).unwrap();
+
+        // Assert
+        assert!(log.starts_with("start -> 0x2113_2113_4455_0000_0000\n"));
+        let expected = path[1..]
+            .iter()
+            .map(|state| {
+                (
+                    state.piece.unwrap(),
+                    state.path.clone(),
+                    state.board.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(moves, expected);
+    }
+
+    #[test]
+    fn test_parse_log_errors() {
+        // Act & Assert
+        assert_eq!(parse_log(""), Err(LogError::MissingStart));
+        assert_eq!(
+            parse_log("7 Down -> 0x2113_2113_4556_4086_970a"),
+            Err(LogError::MissingStart)
+        );
+        assert_eq!(
+            parse_log(
+                "start -> 0x2113_2113_4556_4786_900a\n7 Sideways -> 0x2113_2113_4556_4086_970a"
+            ),
+            Err(LogError::InvalidLine(2))
+        );
+        assert_eq!(
+            parse_log("start -> 0x2113_2113_4556_4786_900a\n\n7 Up -> 0x2113_2113_4556_4086_970a"),
+            Err(LogError::IllegalMove(3))
+        );
+        assert_eq!(
+            parse_log("start -> 0x2113_2113_4556_4786_900a\n17 Down -> 0x2113_2113_4556_4086_970a"),
+            Err(LogError::InvalidLine(2))
+        );
+    }
+}
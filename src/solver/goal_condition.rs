@@ -0,0 +1,54 @@
+use super::BitPattern;
+
+/// The condition that the large piece must satisfy for the puzzle to be finished.
+#[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+pub enum GoalCondition {
+    /// The large piece occupies exactly the cells of the mask.
+    Occupies(BitPattern),
+    /// The large piece occupies none of the cells of the mask.
+    Avoids(BitPattern),
+}
+
+impl GoalCondition {
+    /// Returns the mask of the region the condition refers to.
+    pub fn mask(&self) -> BitPattern {
+        match self {
+            GoalCondition::Occupies(mask) | GoalCondition::Avoids(mask) => *mask,
+        }
+    }
+
+    /// Checks if the given mask of the large piece satisfies the condition.
+    pub fn is_satisfied_by(&self, piece_mask: BitPattern) -> bool {
+        match self {
+            GoalCondition::Occupies(mask) => piece_mask == *mask,
+            GoalCondition::Avoids(mask) => (piece_mask & *mask).is_empty(),
+        }
+    }
+
+    /// Returns the same kind of condition with the region mirrored left-to-right.
+    pub fn mirrored(&self) -> Self {
+        match self {
+            GoalCondition::Occupies(mask) => GoalCondition::Occupies(mask.mirrored()),
+            GoalCondition::Avoids(mask) => GoalCondition::Avoids(mask.mirrored()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_satisfied_by() {
+        // Arrange
+        let bottom_center = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let top_center = BitPattern::new(0x0ff0_0ff0_0000_0000_0000);
+        let top_left = BitPattern::new(0xff00_ff00_0000_0000_0000);
+
+        // Act & Assert
+        assert!(GoalCondition::Occupies(bottom_center).is_satisfied_by(bottom_center));
+        assert!(!GoalCondition::Occupies(bottom_center).is_satisfied_by(top_center));
+        assert!(GoalCondition::Avoids(bottom_center).is_satisfied_by(top_center));
+        assert!(!GoalCondition::Avoids(top_center).is_satisfied_by(top_left));
+    }
+}
@@ -0,0 +1,190 @@
+use super::BitPattern;
+use super::Board;
+use super::Rule;
+use super::State;
+use super::rule::parse_20_hex_digits;
+use super::solve::BoardKey;
+use super::solve::get_neighbors;
+use crate::greedy;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+
+/// Builds a table of the distance to the goal for each canonical board within `max_depth` moves of a goal.
+///
+/// The goal boards reachable from the rule's starting board are collected first,
+/// then a backward breadth-first search is run from all of them at once.
+/// The table is keyed by the canonical pattern of `BoardKey`, so it can be reused
+/// to warm-start searches on the same puzzle; see `goal_table_entries` to persist it.
+pub fn build_goal_table(rule: &Rule, max_depth: usize) -> HashMap<BitPattern, usize> {
+    let mut table = HashMap::new();
+    let mut frontier = vec![];
+    for board in reachable_goal_boards(rule) {
+        if table
+            .insert(BoardKey::create(rule, &board).get_pattern(), 0)
+            .is_none()
+        {
+            frontier.push(board);
+        }
+    }
+
+    for depth in 1..=max_depth {
+        let mut next_frontier = vec![];
+        for board in &frontier {
+            for next_state in get_neighbors(rule, &State::new(board)) {
+                let key = BoardKey::create(rule, &next_state.board).get_pattern();
+                if let Entry::Vacant(entry) = table.entry(key) {
+                    entry.insert(depth);
+                    next_frontier.push(next_state.board);
+                }
+            }
+        }
+        if next_frontier.is_empty() {
+            // All boards that can reach the goal are in the table.
+            break;
+        }
+        frontier = next_frontier;
+    }
+    table
+}
+
+/// Solves the klotski puzzle using a table built by `build_goal_table` as a perfect heuristic.
+///
+/// If the starting board is within the table, the path found is a shortest one.
+pub fn solve_with_table(rule: &Rule, table: &HashMap<BitPattern, usize>) -> Option<Vec<State>> {
+    let start_state = State::new(&rule.start);
+
+    let is_goal = |s: &State| rule.is_finished(&s.board);
+    let neighbors = |s: &State| get_neighbors(rule, s);
+
+    let mut visited = HashSet::new();
+    let try_visit = |s: &State| visited.insert(BoardKey::create(rule, &s.board));
+    let heuristic = |s: &State| {
+        let key = BoardKey::create(rule, &s.board).get_pattern();
+        // Boards outside the table are farther than any board in it.
+        table.get(&key).copied().unwrap_or(usize::MAX)
+    };
+
+    greedy::find_path(&start_state, is_goal, neighbors, try_visit, heuristic)
+}
+
+/// Converts a goal table into entries of hex board images and distances, sorted by image.
+///
+/// Unlike the table itself, the entries can be written as JSON (with the `serde` feature),
+/// since JSON object keys must be strings.
+pub fn goal_table_entries(table: &HashMap<BitPattern, usize>) -> Vec<(String, usize)> {
+    let mut entries: Vec<_> = table
+        .iter()
+        .map(|(pattern, &depth)| (format!("0x{}", pattern.to_hex20()), depth))
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// Restores a goal table from the entries made by `goal_table_entries`.
+///
+/// Returns `None` if an image is not 20 hex digits.
+pub fn goal_table_from_entries(entries: &[(String, usize)]) -> Option<HashMap<BitPattern, usize>> {
+    entries
+        .iter()
+        .map(|(image, depth)| parse_20_hex_digits(image).map(|pattern| (pattern, *depth)))
+        .collect()
+}
+
+/// Collects all goal boards reachable from the rule's starting board.
+fn reachable_goal_boards(rule: &Rule) -> Vec<Board> {
+    let mut visited = HashSet::from([BoardKey::create(rule, &rule.start)]);
+    let mut stack = vec![rule.start.clone()];
+    let mut goal_boards = vec![];
+    while let Some(board) = stack.pop() {
+        if rule.is_finished(&board) {
+            goal_boards.push(board.clone());
+        }
+        for next_state in get_neighbors(rule, &State::new(&board)) {
+            if visited.insert(BoardKey::create(rule, &next_state.board)) {
+                stack.push(next_state.board);
+            }
+        }
+    }
+    goal_boards
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_build_goal_table() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        // Act
+        let table = build_goal_table(&rule, 1);
+        // Assert
+        assert!(table.values().any(|&d| d == 0));
+        assert!(table.values().any(|&d| d == 1));
+        assert!(table.values().all(|&d| d <= 1));
+    }
+
+    #[test]
+    fn test_solve_with_table_matches_bfs_length() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let table = build_goal_table(&rule, usize::MAX);
+        // Act
+        let path = solve_with_table(&rule, &table).unwrap();
+        // Assert
+        let bfs_path = solve(&rule).unwrap();
+        assert_eq!(path.len(), bfs_path.len());
+        assert!(rule.is_finished(&path.last().unwrap().board));
+    }
+
+    #[test]
+    fn test_goal_table_on_full_puzzle() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4006_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let table = build_goal_table(&rule, usize::MAX);
+        // Act
+        let path = solve_with_table(&rule, &table).unwrap();
+        // Assert
+        let bfs_path = solve(&rule).unwrap();
+        let start_key = BoardKey::create(&rule, &rule.start).get_pattern();
+        assert_eq!(table[&start_key], bfs_path.len() - 1);
+        assert_eq!(path.len(), bfs_path.len());
+        assert!(rule.is_finished(&path.last().unwrap().board));
+    }
+
+    #[test]
+    fn test_goal_table_entries_round_trip() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let table = build_goal_table(&rule, usize::MAX);
+        // Act
+        let entries = goal_table_entries(&table);
+        let restored = goal_table_from_entries(&entries).unwrap();
+        // Assert
+        assert_eq!(restored, table);
+        assert!(entries.iter().all(|(image, _)| image.len() == 22));
+        assert!(entries.is_sorted());
+    }
+
+    #[test]
+    fn test_goal_table_from_invalid_entries() {
+        // Arrange
+        let entries = vec![("0xé".to_string(), 0)];
+        // Act & Assert
+        assert_eq!(goal_table_from_entries(&entries), None);
+    }
+}
@@ -0,0 +1,145 @@
+use super::BitPattern;
+use super::Board;
+use super::GoalCondition;
+use super::Piece;
+use super::Rule;
+use super::Shape;
+use super::State;
+use super::rule::top_left_cell;
+use super::shape::classify;
+
+/// The estimates of the remaining moves that `solve_astar` can be guided by.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Heuristic {
+    /// The Manhattan distance of the large piece to the goal, as given by `Rule::goal_distance`,
+    /// halved since a move may carry a piece two cells.
+    Distance,
+    /// The moves of the large piece plus the pieces in its way, as given by `blocking_heuristic`.
+    Blocking,
+}
+
+impl Heuristic {
+    /// Estimates the number of moves needed to finish the board under the rule, never overestimating.
+    ///
+    /// When pieces wrap around the edges, the distances across the board do not bound the moves, so this is always 0.
+    pub fn estimate(&self, rule: &Rule, board: &Board) -> usize {
+        if rule.wrap {
+            return 0;
+        }
+        match (self, rule.goal) {
+            (Heuristic::Distance, _) => rule.goal_distance(board).div_ceil(2),
+            (Heuristic::Blocking, GoalCondition::Occupies(goal_mask)) => {
+                blocking_heuristic(board, &goal_mask, Piece::new(1))
+            }
+            (Heuristic::Blocking, GoalCondition::Avoids(_)) => 0,
+        }
+    }
+}
+
+/// Estimates the number of moves needed to bring the goal piece onto the goal mask, never overestimating.
+///
+/// This is the number of moves the goal piece needs to cover its Manhattan distance to the goal,
+/// plus one move for each other piece occupying the goal mask, since each of them must move out of the way.
+/// Pieces between the goal piece and the goal are not counted, since the goal piece may go around them.
+///
+/// A piece may move two cells in one move, except the large piece while there are fewer than four
+/// empty cells, since it needs two empty cells for each cell it moves.
+pub fn blocking_heuristic(board: &Board, goal_mask: &BitPattern, goal_piece: Piece) -> usize {
+    let piece_mask = board.pattern.mask_of(goal_piece);
+    let distance = match (top_left_cell(&piece_mask), top_left_cell(goal_mask)) {
+        (Some((row, col)), Some((goal_row, goal_col))) => {
+            row.abs_diff(goal_row) + col.abs_diff(goal_col)
+        }
+        _ => 0,
+    };
+    let cells_per_move =
+        if classify(board, goal_piece) == Shape::Large && board.empty_cells().len() < 4 {
+            1
+        } else {
+            2
+        };
+    let blocking_pieces = board
+        .pieces()
+        .into_iter()
+        .filter(|&piece| piece != goal_piece)
+        .filter(|&piece| (board.pattern.mask_of(piece) & *goal_mask).is_not_empty())
+        .count();
+    distance.div_ceil(cells_per_move) + blocking_pieces
+}
+
+/// Estimates, for each board of the path, the number of moves left to bring the goal piece onto the goal mask.
+///
+/// Each entry is the `blocking_heuristic` of the board, so the entries show how a solution approaches
+/// the goal and retreats from it. They are often not monotonic.
+pub fn solution_progress(path: &[State], goal_mask: &BitPattern, goal_piece: Piece) -> Vec<usize> {
+    path.iter()
+        .map(|state| blocking_heuristic(&state.board, goal_mask, goal_piece))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::solve;
+    use super::*;
+
+    #[test]
+    fn test_blocking_heuristic() {
+        // Arrange
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+
+        // Act & Assert
+        assert_eq!(
+            blocking_heuristic(
+                &Board::new(0x2113_2113_4556_4786_900a),
+                &goal_mask,
+                Piece::new(1)
+            ),
+            3 + 2
+        );
+        assert_eq!(
+            blocking_heuristic(
+                &Board::new(0x2003_2113_4116_4556_7789),
+                &goal_mask,
+                Piece::new(1)
+            ),
+            2 + 3
+        );
+        assert_eq!(
+            blocking_heuristic(
+                &Board::new(0x2453_2763_8009_a119_a11b),
+                &goal_mask,
+                Piece::new(1)
+            ),
+            0
+        );
+    }
+
+    #[test]
+    fn test_solution_progress() {
+        // Arrange
+        let goal_mask = BitPattern::new(0xff00_ff00_0000_0000_0000);
+        let rule = Rule::new(&Board::new(0x2113_2113_4556_4786_900a), &goal_mask);
+        let path = solve(&rule).unwrap();
+
+        // Act
+        let progress = solution_progress(&path, &goal_mask, Piece::new(1));
+
+        // Assert
+        assert_eq!(progress.len(), path.len());
+        assert_eq!(
+            progress[0],
+            blocking_heuristic(&rule.start, &goal_mask, Piece::new(1))
+        );
+        assert_eq!(progress.last(), Some(&0));
+    }
+
+    #[test]
+    fn test_blocking_heuristic_with_many_blanks() {
+        // Arrange: With four empty cells, the large piece may move two cells at once
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let board = Board::new(0x2113_2113_4556_0000_0000);
+
+        // Act & Assert
+        assert_eq!(blocking_heuristic(&board, &goal_mask, Piece::new(1)), 2);
+    }
+}
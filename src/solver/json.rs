@@ -0,0 +1,169 @@
+use super::BitPattern;
+use super::Board;
+use super::MovePath;
+use super::Piece;
+use super::State;
+
+/// The change made by one step of a solution: the moved piece and the cells it occupies afterwards.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StepDelta {
+    pub piece: Piece,
+    pub path: MovePath,
+    pub mask: BitPattern,
+}
+
+impl StepDelta {
+    /// Applies the step to the board before it, returning the board after it.
+    pub fn apply(&self, board: &Board) -> Board {
+        let other_pieces = board.pattern & !board.pattern.mask_of(self.piece);
+        let id_pattern = BitPattern::new(self.piece.id as u128 * 0x1111_1111_1111_1111_1111);
+        Board::from_bitpattern(other_pieces | (self.mask & id_pattern))
+    }
+}
+
+/// Returns the change made by each step of the solution path.
+pub fn step_deltas(path: &[State]) -> Vec<StepDelta> {
+    path.iter()
+        .filter_map(|state| {
+            let piece = state.piece?;
+            Some(StepDelta {
+                piece,
+                path: state.path.clone(),
+                mask: state.board.pattern.mask_of(piece),
+            })
+        })
+        .collect()
+}
+
+/// Formats the solution path as JSON, with the full board after each step.
+pub fn to_json(path: &[State]) -> String {
+    let steps = path
+        .iter()
+        .filter_map(|state| {
+            let piece = state.piece?;
+            Some(format!(
+                r#"{{"piece":"{piece}","path":"{}","board":"{}"}}"#,
+                state.path,
+                hex_string(&state.board.pattern)
+            ))
+        })
+        .collect::<Vec<_>>();
+    format!(r#"{{"steps":[{}]}}"#, steps.join(","))
+}
+
+/// Formats the solution path as JSON, with the full starting board once
+/// and only the cells of the moved piece after each step.
+pub fn to_json_delta(path: &[State]) -> String {
+    let start = path
+        .first()
+        .map(|state| hex_string(&state.board.pattern))
+        .unwrap_or_default();
+    let steps = step_deltas(path)
+        .iter()
+        .map(|delta| {
+            format!(
+                r#"{{"piece":"{}","path":"{}","mask":"{}"}}"#,
+                delta.piece,
+                delta.path,
+                hex_string(&delta.mask)
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(r#"{{"start":"{start}","steps":[{}]}}"#, steps.join(","))
+}
+
+/// Formats the bit pattern as 20 hex digits with underscores between rows, as accepted by `Rule::parse`.
+pub(super) fn hex_string(pattern: &BitPattern) -> String {
+    format!("0x{}", pattern.to_string().trim_matches(['[', ']']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    fn sample_path() -> Vec<State> {
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let first = Board::new(0x2113_2113_4556_4086_970a);
+        let second = Board::new(0x2113_2113_4556_4086_97a0);
+        vec![
+            State::new(&start),
+            State {
+                empties: first.empty_mask(),
+                board: first,
+                piece: Some(Piece::new(7)),
+                path: MovePath::One(Direction::Down),
+                earlier_pieces: RecentPieces::default(),
+            },
+            State {
+                empties: second.empty_mask(),
+                board: second,
+                piece: Some(Piece::new(0xa)),
+                path: MovePath::One(Direction::Left),
+                earlier_pieces: RecentPieces::default(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_to_json() {
+        // Arrange
+        let path = sample_path();
+        // Act
+        let json = to_json(&path);
+        // Assert
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"steps":["#,
+                r#"{"piece":"7","path":"Down","board":"0x2113_2113_4556_4086_970a"},"#,
+                r#"{"piece":"a","path":"Left","board":"0x2113_2113_4556_4086_97a0"}"#,
+                r#"]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_to_json_delta() {
+        // Arrange
+        let path = sample_path();
+        // Act
+        let json = to_json_delta(&path);
+        // Assert
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"start":"0x2113_2113_4556_4786_900a","steps":["#,
+                r#"{"piece":"7","path":"Down","mask":"0x0000_0000_0000_0000_0f00"},"#,
+                r#"{"piece":"a","path":"Left","mask":"0x0000_0000_0000_0000_00f0"}"#,
+                r#"]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_step_deltas_reconstruct_boards() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let path = solve(&rule).unwrap();
+
+        // Act
+        let boards = step_deltas(&path)
+            .iter()
+            .scan(rule.start.clone(), |board, delta| {
+                *board = delta.apply(board);
+                Some(board.clone())
+            })
+            .collect::<Vec<_>>();
+
+        // Assert
+        let expected_boards = path[1..]
+            .iter()
+            .map(|state| state.board.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(boards, expected_boards);
+    }
+}
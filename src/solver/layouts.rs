@@ -0,0 +1,71 @@
+//! Named puzzle layouts bundled with the solver.
+
+use super::BitPattern;
+use super::Board;
+use super::Rule;
+
+/// A named starting board and goal, with the length of its shortest solution.
+#[derive(Debug)]
+pub struct Layout {
+    pub name: &'static str,
+    pub start: Board,
+    pub goal_mask: BitPattern,
+    /// The number of moves in a shortest solution, or `None` if the layout is intentionally unsolvable.
+    pub moves: Option<usize>,
+}
+
+impl Layout {
+    /// Creates the rule for solving the layout.
+    pub fn rule(&self) -> Rule {
+        Rule::new(&self.start, &self.goal_mask)
+    }
+}
+
+/// The default goal: the large piece at the bottom center.
+const BOTTOM_CENTER: BitPattern = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+
+static LAYOUTS: &[Layout] = &[
+    Layout {
+        name: "hakoiri-musume",
+        start: Board::new(0x2113_2113_4556_4786_900a),
+        goal_mask: BOTTOM_CENTER,
+        moves: Some(81),
+    },
+    Layout {
+        name: "hua-rong-dao",
+        start: Board::new(0x2113_2113_4556_7896_700a),
+        goal_mask: BOTTOM_CENTER,
+        moves: Some(77),
+    },
+    Layout {
+        name: "locked-rows",
+        start: Board::new(0x2113_2113_4455_6677_8009),
+        goal_mask: BOTTOM_CENTER,
+        moves: None,
+    },
+];
+
+/// Returns all bundled layouts.
+pub fn all() -> &'static [Layout] {
+    LAYOUTS
+}
+
+/// Returns the bundled layout with the given name.
+pub fn find(name: &str) -> Option<&'static Layout> {
+    LAYOUTS.iter().find(|layout| layout.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        // Act & Assert
+        assert_eq!(
+            find("hakoiri-musume").map(|layout| &layout.start),
+            Some(&Board::new(0x2113_2113_4556_4786_900a))
+        );
+        assert!(find("unknown").is_none());
+    }
+}
@@ -7,6 +7,26 @@ pub enum MovePath {
     Two(Direction, Direction),
 }
 
+impl MovePath {
+    /// Returns the directions of the path, in the order they are moved.
+    pub fn directions(&self) -> Vec<Direction> {
+        match self {
+            MovePath::None => vec![],
+            MovePath::One(d) => vec![*d],
+            MovePath::Two(d1, d2) => vec![*d1, *d2],
+        }
+    }
+
+    /// Returns the path that undoes this one: the directions reversed, in the opposite order.
+    pub fn reversed(&self) -> MovePath {
+        match self {
+            MovePath::None => MovePath::None,
+            MovePath::One(d) => MovePath::One(d.reversed()),
+            MovePath::Two(d1, d2) => MovePath::Two(d2.reversed(), d1.reversed()),
+        }
+    }
+}
+
 impl std::fmt::Display for MovePath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -34,4 +54,32 @@ mod tests {
         assert_eq!(format!("{path_one}"), format!("{up}"));
         assert_eq!(format!("{path_two}"), format!("{up} and {down}"));
     }
+
+    #[test]
+    fn test_move_path_directions() {
+        // Act & Assert
+        assert_eq!(MovePath::None.directions(), vec![]);
+        assert_eq!(
+            MovePath::One(Direction::Left).directions(),
+            vec![Direction::Left]
+        );
+        assert_eq!(
+            MovePath::Two(Direction::Up, Direction::Right).directions(),
+            vec![Direction::Up, Direction::Right]
+        );
+    }
+
+    #[test]
+    fn test_move_path_reversed() {
+        // Act & Assert
+        assert_eq!(MovePath::None.reversed(), MovePath::None);
+        assert_eq!(
+            MovePath::One(Direction::Left).reversed(),
+            MovePath::One(Direction::Right)
+        );
+        assert_eq!(
+            MovePath::Two(Direction::Up, Direction::Right).reversed(),
+            MovePath::Two(Direction::Left, Direction::Down)
+        );
+    }
 }
@@ -0,0 +1,197 @@
+//! A precomputed solution of the default puzzle, so that `solve` can return it without searching.
+
+use super::BitPattern;
+use super::Board;
+use super::Direction::{Down, Left, Right, Up};
+use super::MovePath::{self, One, Two};
+use super::Piece;
+use super::Rule;
+use super::State;
+use super::solve::next_earlier_pieces;
+
+/// The starting board of the default puzzle.
+const BOOK_START: Board = Board::new(0x2113_2113_4556_4786_900a);
+/// The goal of the default puzzle: the large piece at the bottom center.
+const BOOK_GOAL: BitPattern = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+
+/// The moves of the shortest solution of the default puzzle found by `solve`, as piece IDs and paths.
+static BOOK_MOVES: &[(u8, MovePath)] = &[
+    (7, One(Down)),
+    (0xa, One(Left)),
+    (6, One(Down)),
+    (5, One(Right)),
+    (4, One(Right)),
+    (9, One(Up)),
+    (7, One(Left)),
+    (4, One(Down)),
+    (5, Two(Left, Left)),
+    (8, Two(Up, Right)),
+    (0xa, Two(Up, Up)),
+    (4, One(Right)),
+    (9, Two(Right, Down)),
+    (5, One(Down)),
+    (0xa, Two(Left, Left)),
+    (8, Two(Left, Left)),
+    (4, One(Up)),
+    (6, One(Up)),
+    (9, Two(Right, Right)),
+    (7, Two(Right, Right)),
+    (5, One(Down)),
+    (8, Two(Down, Left)),
+    (4, One(Left)),
+    (6, One(Left)),
+    (3, Two(Down, Down)),
+    (1, One(Right)),
+    (2, One(Right)),
+    (0xa, Two(Up, Up)),
+    (8, Two(Up, Up)),
+    (4, One(Left)),
+    (2, Two(Down, Down)),
+    (1, One(Left)),
+    (3, Two(Up, Up)),
+    (6, One(Right)),
+    (7, Two(Up, Up)),
+    (9, Two(Left, Up)),
+    (5, Two(Right, Right)),
+    (2, One(Down)),
+    (4, One(Down)),
+    (7, Two(Left, Left)),
+    (1, One(Down)),
+    (0xa, Two(Right, Right)),
+    (8, Two(Up, Right)),
+    (7, Two(Up, Up)),
+    (4, Two(Up, Up)),
+    (2, One(Left)),
+    (9, Two(Left, Down)),
+    (1, One(Down)),
+    (0xa, Two(Down, Left)),
+    (3, One(Left)),
+    (6, Two(Up, Up)),
+    (1, One(Right)),
+    (0xa, One(Down)),
+    (8, One(Down)),
+    (7, One(Right)),
+    (4, One(Up)),
+    (2, One(Up)),
+    (9, One(Left)),
+    (0xa, Two(Down, Down)),
+    (1, One(Left)),
+    (6, Two(Down, Down)),
+    (3, One(Right)),
+    (7, One(Right)),
+    (8, One(Right)),
+    (4, One(Right)),
+    (2, Two(Up, Up)),
+    (1, One(Left)),
+    (8, Two(Down, Down)),
+    (7, Two(Down, Down)),
+    (3, One(Left)),
+    (6, Two(Up, Up)),
+    (8, Two(Right, Up)),
+    (5, One(Up)),
+    (0xa, Two(Right, Right)),
+    (9, Two(Right, Right)),
+    (1, One(Down)),
+    (7, Two(Left, Left)),
+    (8, Two(Left, Left)),
+    (5, One(Up)),
+    (9, Two(Up, Right)),
+    (1, One(Right)),
+];
+
+/// Returns the solution from the book if the rule is the default puzzle with the default options.
+///
+/// The path is the same as the one found by searching, including the order of the equally short paths
+/// and the earlier pieces recorded in each state.
+pub(super) fn lookup(rule: &Rule) -> Option<Vec<State>> {
+    // The cache key leaves out the pairs and the symmetries, and folds the mirror image of the starting board.
+    if rule.start != BOOK_START {
+        return None;
+    }
+    let book_rule = Rule::new(&BOOK_START, &BOOK_GOAL);
+    let sorted_pairs = |rule: &Rule| {
+        let mut pairs = rule.pairs.clone();
+        pairs.sort();
+        pairs
+    };
+    let is_default = rule.cache_key() == book_rule.cache_key()
+        && sorted_pairs(rule) == sorted_pairs(&book_rule)
+        && rule.horizontal_symmetry == book_rule.horizontal_symmetry
+        && rule.vertical_symmetry == book_rule.vertical_symmetry;
+    if !is_default {
+        return None;
+    }
+
+    let mut path = vec![State::new(&BOOK_START)];
+    for (id, move_path) in BOOK_MOVES {
+        let state = path.last()?;
+        let piece = Piece::new(*id);
+        let board = state.board.apply(piece, move_path)?;
+        let earlier_pieces = next_earlier_pieces(rule, state);
+        path.push(State {
+            empties: board.empty_mask(),
+            board,
+            piece: Some(piece),
+            path: move_path.clone(),
+            earlier_pieces,
+        });
+    }
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_lookup_matches_search() {
+        // Arrange
+        let rule = Rule::new(&BOOK_START, &BOOK_GOAL);
+
+        // Act
+        let book_path = lookup(&rule).unwrap();
+        let (searched_path, _stats) = solve_with_stats(&rule);
+
+        // Assert
+        assert_eq!(book_path.len(), 82);
+        assert_eq!(Some(book_path.clone()), searched_path);
+        assert!(verify_solution(&rule, &book_path));
+        assert_eq!(solve(&rule), Some(book_path));
+    }
+
+    #[test]
+    fn test_lookup_skips_other_rules() {
+        // Arrange
+        let variant = |change: fn(&mut Rule)| {
+            let mut rule = Rule::new(&BOOK_START, &BOOK_GOAL);
+            change(&mut rule);
+            rule
+        };
+
+        // Act & Assert
+        assert!(lookup(&variant(|rule| rule.direction_order.reverse())).is_none());
+        assert!(lookup(&variant(|rule| rule.pairs.clear())).is_none());
+        assert!(lookup(&variant(|rule| rule.horizontal_symmetry = false)).is_none());
+        assert!(lookup(&variant(|rule| rule.vertical_symmetry = true)).is_none());
+        assert!(lookup(&variant(|rule| rule.allow_reversal = true)).is_none());
+        assert!(lookup(&variant(|rule| rule.tabu_window = 2)).is_none());
+        assert!(lookup(&variant(|rule| rule.wrap = true)).is_none());
+        assert!(lookup(&variant(|rule| rule.locked = vec![Piece::new(9)])).is_none());
+        assert!(
+            lookup(&Rule::new(
+                &Board::new(0x2113_2113_4556_7896_700a),
+                &BOOK_GOAL
+            ))
+            .is_none()
+        );
+        // The mirror image has the same cache key, but not the same path.
+        assert!(
+            lookup(&Rule::new(
+                &Board::new(0x3112_3112_6554_6874_a009),
+                &BOOK_GOAL
+            ))
+            .is_none()
+        );
+    }
+}
@@ -1,13 +1,38 @@
 #[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u8", into = "u8"))]
 pub struct Piece {
     pub id: u8,
 }
 
 impl Piece {
     /// Creates a new `Piece` with the given ID.
+    ///
+    /// The ID must be at most 0xf, since each cell of a board holds it in 4 bits. A larger ID silently
+    /// corrupts the masks computed for it, so use `try_new` for IDs read from input.
     pub const fn new(id: u8) -> Self {
         Self { id }
     }
+
+    /// Creates a new `Piece` with the given ID, or returns `None` if the ID does not fit in 4 bits.
+    pub const fn try_new(id: u8) -> Option<Self> {
+        if id <= 0xf { Some(Self { id }) } else { None }
+    }
+}
+
+impl TryFrom<u8> for Piece {
+    type Error = String;
+
+    /// Creates a new `Piece` with the given ID like `try_new`, so deserialized pieces are checked as well.
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        Self::try_new(id).ok_or_else(|| format!("piece ID {id:#x} does not fit in 4 bits"))
+    }
+}
+
+impl From<Piece> for u8 {
+    fn from(piece: Piece) -> Self {
+        piece.id
+    }
 }
 
 impl std::fmt::Display for Piece {
@@ -31,4 +56,28 @@ mod tests {
 
         assert_eq!(format!("{}", Piece::new(0xa)), "a");
     }
+
+    #[test]
+    fn test_try_new() {
+        // Act & Assert
+        assert_eq!(Piece::try_new(0), Some(Piece::new(0)));
+        assert_eq!(Piece::try_new(15), Some(Piece::new(0xf)));
+        assert_eq!(Piece::try_new(16), None);
+        assert_eq!(Piece::try_new(u8::MAX), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_checks_id() {
+        use serde::Deserialize;
+        use serde::de::value::Error;
+        use serde::de::value::U8Deserializer;
+
+        // Act & Assert
+        assert_eq!(
+            Piece::deserialize(U8Deserializer::<Error>::new(0xf)),
+            Ok(Piece::new(0xf))
+        );
+        assert!(Piece::deserialize(U8Deserializer::<Error>::new(0x10)).is_err());
+    }
 }
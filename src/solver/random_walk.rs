@@ -0,0 +1,188 @@
+use super::Board;
+use super::MovePath;
+use super::Piece;
+use super::RecentPieces;
+use super::Rule;
+use super::State;
+
+/// Takes `steps` random legal moves from the starting board, for demos and generating test data.
+///
+/// As in the solver, the piece moved last is never moved again immediately. Moves are picked with a
+/// small pseudo-random generator seeded by `seed`, so the same seed always gives the same walk.
+///
+/// Returns the states from the start, which is shorter than `steps + 1` if no piece can move.
+pub fn random_walk(start: &Board, steps: usize, seed: u64) -> Vec<State> {
+    walk_with(start, steps, seed, Board::legal_moves)
+}
+
+/// Takes `steps` random moves from the starting board of the rule, like `random_walk`.
+///
+/// Only the moves the rule allows are taken: locked pieces never move, pieces wrap around the edges if
+/// the rule wraps, and no move stops on or passes through a forbidden board.
+pub fn random_walk_with_rule(rule: &Rule, steps: usize, seed: u64) -> Vec<State> {
+    walk_with(&rule.start, steps, seed, |board| {
+        rule.legal_moves(board)
+            .into_iter()
+            .filter(|(piece, path, next_board)| {
+                let passed = match *path {
+                    MovePath::Two(first, _) => rule.move_piece(board, *piece, first),
+                    _ => None,
+                };
+                !rule.is_forbidden(next_board)
+                    && passed.is_none_or(|passed| !rule.is_forbidden(&passed))
+            })
+            .collect()
+    })
+}
+
+/// Takes `steps` random moves from the starting board, picking each from the moves given by `legal_moves`.
+fn walk_with(
+    start: &Board,
+    steps: usize,
+    seed: u64,
+    legal_moves: impl Fn(&Board) -> Vec<(Piece, MovePath, Board)>,
+) -> Vec<State> {
+    let mut rng = SplitMix64(seed);
+    let mut walk = vec![State::new(start)];
+    for _ in 0..steps {
+        let state = walk.last().unwrap();
+        let moves = legal_moves(&state.board)
+            .into_iter()
+            .filter(|&(piece, _, _)| state.piece != Some(piece))
+            .collect::<Vec<_>>();
+        if moves.is_empty() {
+            break;
+        }
+        let index = (rng.next() % moves.len() as u64) as usize;
+        let (piece, path, board) = moves[index].clone();
+        walk.push(State {
+            empties: board.empty_mask(),
+            board,
+            piece: Some(piece),
+            path,
+            earlier_pieces: RecentPieces::default(),
+        });
+    }
+    walk
+}
+
+/// The SplitMix64 pseudo-random number generator, which is enough for picking moves.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_random_walk() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act
+        let walk = random_walk(&start, 100, 42);
+
+        // Assert
+        assert!(walk.len() <= 101);
+        assert_eq!(walk[0], State::new(&start));
+        let last = walk.last().unwrap();
+        if walk.len() < 101 {
+            // Stopped because only the piece moved last could move.
+            for (piece, _, _) in last.board.legal_moves() {
+                assert_eq!(last.piece, Some(piece));
+            }
+        }
+        for pair in walk.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            assert!(next.board.is_valid());
+            assert_ne!(prev.piece, next.piece);
+            assert!(prev.board.legal_moves().contains(&(
+                next.piece.unwrap(),
+                next.path.clone(),
+                next.board.clone()
+            )));
+        }
+    }
+
+    #[test]
+    fn test_random_walk_is_deterministic() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act
+        let walk = random_walk(&start, 50, 7);
+
+        // Assert
+        assert_eq!(walk, random_walk(&start, 50, 7));
+        assert_ne!(walk, random_walk(&start, 50, 8));
+    }
+
+    #[test]
+    fn test_random_walk_with_rule_keeps_locked_pieces() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let goal = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let rule = Rule::with_locked(&start, &goal, vec![Piece::new(7)]).unwrap();
+
+        // Act
+        let walk = random_walk_with_rule(&rule, 100, 42);
+
+        // Assert
+        assert!(walk.len() > 1);
+        let locked_mask = start.pattern.mask_of(Piece::new(7));
+        for pair in walk.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            assert_ne!(next.piece, Some(Piece::new(7)));
+            assert_eq!(next.board.pattern.mask_of(Piece::new(7)), locked_mask);
+            assert!(rule.legal_moves(&prev.board).contains(&(
+                next.piece.unwrap(),
+                next.path.clone(),
+                next.board.clone()
+            )));
+        }
+    }
+
+    #[test]
+    fn test_random_walk_with_rule_avoids_forbidden_boards() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let goal = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let mut rule = Rule::new(&start, &goal);
+        let walk = random_walk_with_rule(&rule, 20, 3);
+        for state in &walk[1..] {
+            rule.forbidden
+                .insert(BoardKey::create(&rule, &state.board).get_pattern());
+        }
+
+        // Act
+        let avoiding = random_walk_with_rule(&rule, 20, 3);
+
+        // Assert
+        assert!(avoiding.len() > 1);
+        for state in &avoiding[1..] {
+            assert!(!rule.is_forbidden(&state.board));
+        }
+    }
+
+    #[test]
+    fn test_random_walk_stuck() {
+        // Arrange: The large piece is the only piece, and cannot move twice in a row
+        let start = Board::new(0x1100_1100_0000_0000_0000);
+
+        // Act
+        let walk = random_walk(&start, 10, 0);
+
+        // Assert
+        assert_eq!(walk.len(), 2);
+    }
+}
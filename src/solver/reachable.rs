@@ -0,0 +1,208 @@
+use super::BitPattern;
+use super::Board;
+use super::Piece;
+use super::Rule;
+use super::State;
+use super::solve::get_neighbors;
+use super::solve::pack_shape_classes;
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+/// Finds every position the large piece can reach from the starting board.
+///
+/// All boards reachable from the start are visited, so any of the returned masks can be used as a
+/// solvable goal. Unlike `solve`, mirror images are not folded, since the mirrored board is not
+/// always reachable.
+///
+/// Returns the distinct masks of piece #1, in ascending order.
+pub fn reachable_goal_positions(start: &Board) -> Vec<BitPattern> {
+    // Pieces of the same shape are interchangeable, unless some piece has an irregular shape.
+    let packable = pack_shape_classes(start.pattern).is_some();
+    let key = |board: &Board| match packable {
+        true => pack_shape_classes(board.pattern).unwrap_or_default() as u128,
+        false => board.pattern.get_u128(),
+    };
+
+    let mut visited = HashSet::from([key(start)]);
+    let mut queue = VecDeque::from([start.clone()]);
+    let mut positions = BTreeSet::new();
+    while let Some(board) = queue.pop_front() {
+        positions.insert(board.pattern.mask_of(Piece::new(1)));
+        for (_, _, next_board) in board.neighbors_iter() {
+            if visited.insert(key(&next_board)) {
+                queue.push_back(next_board);
+            }
+        }
+    }
+    positions.into_iter().collect()
+}
+
+/// Finds the position of the large piece that takes the most moves to reach from the starting board.
+///
+/// All boards reachable from the start are visited breadth-first with the moves allowed by `solve`, which
+/// never moves the same piece twice in a row, recording the fewest moves after which each position of
+/// piece #1 is first reached. That is the length of the shortest solution for the goal mask of the position.
+/// When several positions take as many moves, the one reached first is returned.
+///
+/// Returns the mask of piece #1 at the hardest position and its number of moves, or `None` if piece #1
+/// is not on the board.
+pub fn hardest_goal(start: &Board) -> Option<(BitPattern, usize)> {
+    let goal_piece = Piece::new(1);
+    let start_mask = start.pattern.mask_of(goal_piece);
+    if start_mask.is_empty() {
+        return None;
+    }
+    // Only the moves matter, not the goal of the rule.
+    let rule = Rule::new(start, &start_mask);
+
+    // As in `solve`, a board is visited once, whichever piece was moved last.
+    // Pieces of the same shape are interchangeable, unless some piece has an irregular shape.
+    let packable = pack_shape_classes(start.pattern).is_some();
+    let key = |board: &Board| match packable {
+        true => pack_shape_classes(board.pattern).unwrap_or_default() as u128,
+        false => board.pattern.get_u128(),
+    };
+    let mut visited = HashSet::from([key(start)]);
+    let mut queue = VecDeque::from([(State::new(start), 0)]);
+    let mut positions = HashSet::from([start_mask]);
+    let mut hardest = (start_mask, 0);
+    while let Some((state, depth)) = queue.pop_front() {
+        for next_state in get_neighbors(&rule, &state) {
+            if !visited.insert(key(&next_state.board)) {
+                continue;
+            }
+            // Boards are visited in order of depth, so a new position is the hardest so far.
+            let mask = next_state.board.pattern.mask_of(goal_piece);
+            if positions.insert(mask) && depth + 1 > hardest.1 {
+                hardest = (mask, depth + 1);
+            }
+            queue.push_back((next_state, depth + 1));
+        }
+    }
+    Some(hardest)
+}
+
+/// Counts the distinct boards reachable from the starting board, including the start itself.
+///
+/// Every reachable board is kept in the visited set, so the count is exact, but takes memory in
+/// proportion to it. Boards that differ only by their mirror image, or by swapping pieces of the
+/// same shape, are counted once.
+pub fn count_reachable_states(start: &Board) -> usize {
+    let packable = pack_shape_classes(start.pattern).is_some();
+    let key = |pattern: BitPattern| match packable {
+        true => pack_shape_classes(pattern).unwrap_or_default() as u128,
+        false => pattern.get_u128(),
+    };
+    let canonical = |board: &Board| key(board.pattern).min(key(board.pattern.mirrored()));
+
+    let mut visited = HashSet::from([canonical(start)]);
+    let mut queue = VecDeque::from([start.clone()]);
+    while let Some(board) = queue.pop_front() {
+        for (_, _, next_board) in board.neighbors_iter() {
+            if visited.insert(canonical(&next_board)) {
+                queue.push_back(next_board);
+            }
+        }
+    }
+    visited.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::solve;
+    use super::*;
+
+    #[test]
+    fn test_reachable_goal_positions() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+
+        // Act
+        let positions = reachable_goal_positions(&start);
+
+        // Assert
+        assert!(positions.contains(&start.pattern.mask_of(Piece::new(1))));
+        assert!(positions.contains(&goal_mask));
+    }
+
+    #[test]
+    fn test_reachable_goal_positions_for_unsolvable_board() {
+        // Arrange: The large piece can move, but never reach the bottom center
+        let start = Board::new(0x2113_2113_4455_6677_8009);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+
+        // Act
+        let positions = reachable_goal_positions(&start);
+
+        // Assert
+        assert!(positions.len() > 1);
+        assert!(!positions.contains(&goal_mask));
+    }
+
+    #[test]
+    fn test_hardest_goal() {
+        // Arrange: The large piece alone may not move twice in a row, so it only makes a single move
+        let start = Board::new(0x1100_1100_0000_0000_0000);
+
+        // Act
+        let hardest = hardest_goal(&start);
+
+        // Assert
+        assert_eq!(
+            hardest,
+            Some((BitPattern::new(0x0000_ff00_ff00_0000_0000), 1))
+        );
+        let far_corner = BitPattern::new(0x0000_0000_0000_00ff_00ff);
+        assert_eq!(solve(&Rule::new(&start, &far_corner)), None);
+    }
+
+    #[test]
+    fn test_hardest_goal_matches_solve() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4455_0000_0000);
+
+        // Act
+        let (goal_mask, moves) = hardest_goal(&start).unwrap();
+
+        // Assert
+        let path = solve(&Rule::new(&start, &goal_mask)).unwrap();
+        assert_eq!(path.len() - 1, moves);
+        for position in reachable_goal_positions(&start) {
+            let path = solve(&Rule::new(&start, &position)).unwrap();
+            assert!(path.len() - 1 <= moves);
+        }
+    }
+
+    #[test]
+    fn test_hardest_goal_matches_solve_on_default_board() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act
+        let (goal_mask, moves) = hardest_goal(&start).unwrap();
+
+        // Assert
+        let path = solve(&Rule::new(&start, &goal_mask)).unwrap();
+        assert_eq!(path.len() - 1, moves);
+    }
+
+    #[test]
+    fn test_count_reachable_states() {
+        // Arrange: The large piece alone fits in 3 columns and 4 rows, and the left and right columns mirror each other
+        let start = Board::new(0x1100_1100_0000_0000_0000);
+
+        // Act & Assert
+        assert_eq!(count_reachable_states(&start), 8);
+    }
+
+    #[test]
+    fn test_count_reachable_states_default() {
+        // Arrange: The 25,955 positions known to be reachable, with mirror images counted once
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act & Assert
+        assert_eq!(count_reachable_states(&start), 13011);
+    }
+}
@@ -1,61 +1,190 @@
 use super::BitPattern;
 use super::Board;
+use super::Direction;
+use super::GoalCondition;
+use super::MovePath;
 use super::Piece;
+use super::Shape;
+use super::direction::ALL_DIRECTIONS;
+use super::shape::SHAPE_LARGE;
+use super::shape::classify;
+use super::shape::piece_shape;
+use super::solve::BoardKey;
+use super::solve::solve_from;
 use std::collections::HashMap;
+use std::collections::HashSet;
 
-/// Rule struct holds the puzzle's initial state, piece list, symmetry pairs, and goal mask.
-#[derive(Debug)]
+/// Rule struct holds the puzzle's initial state, piece list, symmetry pairs, goal mask, and move restrictions.
+///
+/// With the `serde` feature, only the starting board, the goal and the options are stored.
+/// The pieces, symmetry pairs and vertical symmetry are derived again when deserializing.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "RuleData", try_from = "RuleData")
+)]
 pub struct Rule {
     pub start: Board,
+    /// The pieces to move, in the order their moves are explored.
     pub pieces: Vec<Piece>,
     pub pairs: Vec<(Piece, Piece)>,
-    pub goal_mask: BitPattern,
+    pub goal: GoalCondition,
+    /// Other pieces that must also occupy exactly the given masks for the puzzle to be finished.
+    pub piece_goals: Vec<(Piece, BitPattern)>,
+    /// The number of turns during which a moved piece may not be moved again.
+    pub tabu_window: usize,
+    /// Whether the goal is symmetric left-to-right, so mirrored boards are equivalent.
+    pub horizontal_symmetry: bool,
+    /// Whether the goal is symmetric top-to-bottom, so vertically flipped boards are equivalent.
+    ///
+    /// A 2x2 region on 5 rows is never symmetric top-to-bottom, so this only holds for `GoalCondition::Avoids`,
+    /// e.g. a large piece that must leave the middle row.
+    pub vertical_symmetry: bool,
+    /// The cost of moving each piece, used by `solve_weighted`. Pieces not listed cost 1.
+    pub weights: HashMap<Piece, usize>,
+    /// The order in which the directions of each piece are explored.
+    ///
+    /// Along with the order of `pieces`, this decides which path is found when several are equally short.
+    pub direction_order: Vec<Direction>,
+    /// Pieces fixed in place, which block other pieces but are never moved.
+    pub locked: Vec<Piece>,
+    /// Whether pieces wrap around the edges of the board, as on a torus, instead of being blocked by them.
+    ///
+    /// A piece moved across an edge is split into parts on opposite sides of the board. `Board::to_svg` draws
+    /// such a piece over the box spanning its parts, and `goal_distance` and `blocking_heuristic` measure from
+    /// its top-left cell, so they only describe pieces that are not split.
+    pub wrap: bool,
+    /// Whether the second step of a double move may reverse the first, returning the piece to where it was.
+    ///
+    /// Such a move never shortens a path, but may matter for variants that count turns rather than boards.
+    pub allow_reversal: bool,
+    /// The canonical patterns of boards that may not be passed through, as by `BoardKey::get_pattern`.
+    ///
+    /// The keys depend on the symmetry pairs and vertical symmetry, so they must be created after those are set.
+    /// The starting board is not checked.
+    pub forbidden: HashSet<BitPattern>,
+}
+
+/// A key identifying a puzzle by every field that affects its search, for use in maps such as a solution cache.
+///
+/// A rule and its mirror image, with the starting board, the goals and the forbidden boards mirrored,
+/// have the same key.
+#[derive(Clone, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+pub struct RuleKey {
+    /// The canonical pattern of the starting board, as by `BoardKey::get_pattern`.
+    start: BitPattern,
+    pieces: Vec<Piece>,
+    goal: GoalCondition,
+    piece_goals: Vec<(Piece, BitPattern)>,
+    tabu_window: usize,
+    weights: Vec<(Piece, usize)>,
+    direction_order: Vec<Direction>,
+    locked: Vec<Piece>,
+    wrap: bool,
+    allow_reversal: bool,
+    forbidden: Vec<BitPattern>,
 }
 
 /// Defines various errors that may occur during rule parsing.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum RuleError {
     InvalidStartBoardHexLength,
-    StartBoardInvalidEmptyCount,
+    /// The starting board does not have the expected number of empty spaces.
+    StartBoardInvalidEmptyCount {
+        found: usize,
+        expected: usize,
+    },
     FirstPieceMissingInStartBoard,
-    InvalidPieceShape,
+    /// A piece other than the large piece has a shape other than small, horizontal or vertical,
+    /// or a piece wraps from one row to the next.
+    InvalidPieceShape {
+        piece: Piece,
+    },
     InvalidGoalMaskHexLength,
+    /// The goal mask does not cover exactly as many cells as the large piece.
     GoalmaskInvalidError,
+    /// The goal mask is not a 2x2 square, so the large piece could never fit in it.
     GoalMaskShapeError,
+    /// The large piece is locked, so it can never reach the goal.
+    LockedGoalPiece,
+    /// The goal position is not a row and column where the large piece fits within the board.
+    InvalidGoalPosition,
+    /// A forbidden board does not fit in 20 hex digits.
+    InvalidForbiddenHexLength,
 }
 
-/// The shape representing a space without a piece.
-const SHAPE_UNUSED: BitPattern = BitPattern::new(0x0000_0000);
-/// The shape of a small piece, which occupies a single cell in the puzzle.
-const SHAPE_SMALL: BitPattern = BitPattern::new(0x0000_000f);
-/// The shape of a horizontally elongated piece, which occupies two columns in the puzzle.
-const SHAPE_HORIZONTAL: BitPattern = BitPattern::new(0x0000_00ff);
-/// The shape of a vertically elongated piece, which occupies two rows in the puzzle.
-const SHAPE_VERTICAL: BitPattern = BitPattern::new(0x000f_000f);
-/// The shape of the large piece to be moved to the goal.
-const SHAPE_LARGE: BitPattern = BitPattern::new(0x00ff_00ff);
+impl std::fmt::Display for RuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            RuleError::InvalidStartBoardHexLength => "START_IMAGE must fit in 20 hex digits.",
+            RuleError::StartBoardInvalidEmptyCount { .. } => {
+                "START_IMAGE must have only two empty spaces."
+            }
+            RuleError::FirstPieceMissingInStartBoard => "START_IMAGE must have the #1 large piece.",
+            RuleError::InvalidPieceShape { .. } => "START_IMAGE contains an invalid piece shape.",
+            RuleError::InvalidGoalMaskHexLength => "GOAL_MASK must fit in 20 hex digits.",
+            RuleError::GoalmaskInvalidError => {
+                "GOAL_MASK is an invalid mask for the goal positions."
+            }
+            RuleError::GoalMaskShapeError => {
+                "GOAL_MASK must be a 2x2 square of `f` digits to hold the #1 large piece."
+            }
+            RuleError::LockedGoalPiece => "The #1 large piece cannot be locked.",
+            RuleError::InvalidGoalPosition => {
+                "GOAL_AT must be the row and column where the #1 large piece fits, e.g. `3,1`."
+            }
+            RuleError::InvalidForbiddenHexLength => "A forbidden board must fit in 20 hex digits.",
+        };
+        write!(f, "{message}")
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/// The number of empty spaces required on the starting board.
+const EMPTY_SPACES: usize = 2;
 
 impl Rule {
     /// Parses the starting board and goal mask from hexadecimal strings, validating their formats and contents.
     pub fn parse(start_image: &str, goal_mask: &str) -> Result<Self, RuleError> {
+        let start_board = Self::parse_start_board(start_image)?;
+        let goal_mask = Self::parse_goal_mask(goal_mask)?;
+        let rule = Self::new(&start_board, &goal_mask);
+
+        Ok(rule)
+    }
+
+    /// Parses the starting board from a hexadecimal string, validating its empty spaces and piece shapes.
+    fn parse_start_board(start_image: &str) -> Result<Board, RuleError> {
         let start_image =
             parse_20_hex_digits(start_image).ok_or(RuleError::InvalidStartBoardHexLength)?;
 
-        if count_empty_spaces(&start_image) != 2 {
-            return Err(RuleError::StartBoardInvalidEmptyCount);
+        let empty_spaces = count_empty_spaces(&start_image);
+        if empty_spaces != EMPTY_SPACES {
+            return Err(RuleError::StartBoardInvalidEmptyCount {
+                found: empty_spaces,
+                expected: EMPTY_SPACES,
+            });
         }
 
-        if piece_shape(&start_image, 1) != SHAPE_LARGE {
+        let start_board = Board::from_bitpattern(start_image);
+        if classify(&start_board, Piece::new(1)) != Shape::Large {
             return Err(RuleError::FirstPieceMissingInStartBoard);
         }
 
-        for i in 0x2u8..=0xf {
-            let shape = piece_shape(&start_image, i);
-            if !is_valid_regular_piece_shapes(&shape) {
-                return Err(RuleError::InvalidPieceShape);
+        for i in 0x1u8..=0xf {
+            let piece = Piece::new(i);
+            if !start_board.has_valid_shape(piece) {
+                return Err(RuleError::InvalidPieceShape { piece });
             }
         }
 
+        Ok(start_board)
+    }
+
+    /// Parses the goal mask from a hexadecimal string, validating that it can hold the large piece.
+    fn parse_goal_mask(goal_mask: &str) -> Result<BitPattern, RuleError> {
         let goal_mask =
             parse_20_hex_digits(goal_mask).ok_or(RuleError::InvalidGoalMaskHexLength)?;
 
@@ -63,39 +192,355 @@ impl Rule {
             return Err(RuleError::GoalmaskInvalidError);
         }
 
-        if piece_shape(&goal_mask, 0xf) != SHAPE_LARGE {
+        if classify(&Board::from_bitpattern(goal_mask), Piece::new(0xf)) != Shape::Large {
             return Err(RuleError::GoalMaskShapeError);
         }
 
-        let rule = Self::new(&Board::from_bitpattern(start_image), &goal_mask);
-
-        Ok(rule)
+        Ok(goal_mask)
     }
 
     /// Create a new Rule from the start board and goal mask.
     pub fn new(start_board: &Board, goal_mask: &BitPattern) -> Self {
+        Self::with_goal(start_board, GoalCondition::Occupies(*goal_mask))
+    }
+
+    /// Create a new Rule from the start board and goal condition.
+    pub fn with_goal(start_board: &Board, goal: GoalCondition) -> Self {
         let pieces = Self::create_pieces(start_board);
-        let pairs = Self::create_pairs(start_board, goal_mask, &pieces);
+        let pairs = symmetry_pairs(start_board, &goal.mask());
+        let horizontal_symmetry = Self::is_horizontally_symmetric(&goal.mask());
+        let vertical_symmetry = Self::is_vertically_symmetric(&goal.mask());
         Self {
             start: start_board.clone(),
             pieces,
             pairs,
-            goal_mask: *goal_mask,
+            goal,
+            piece_goals: vec![],
+            tabu_window: 1,
+            horizontal_symmetry,
+            vertical_symmetry,
+            weights: HashMap::new(),
+            direction_order: ALL_DIRECTIONS.to_vec(),
+            locked: vec![],
+            wrap: false,
+            allow_reversal: false,
+            forbidden: HashSet::new(),
+        }
+    }
+
+    /// Create a new Rule where other pieces must also be positioned along with the large piece.
+    ///
+    /// Symmetry pairs are not used, since swapping a piece with its pair could move it off its goal.
+    /// Boards are only folded left-to-right or top-to-bottom if all the masks are symmetric that way.
+    pub fn with_piece_goals(
+        start_board: &Board,
+        goal_mask: &BitPattern,
+        piece_goals: Vec<(Piece, BitPattern)>,
+    ) -> Self {
+        let mut rule = Self::new(start_board, goal_mask);
+        rule.pairs = vec![];
+        rule.horizontal_symmetry &= piece_goals
+            .iter()
+            .all(|(_, mask)| Self::is_horizontally_symmetric(mask));
+        rule.vertical_symmetry &= piece_goals
+            .iter()
+            .all(|(_, mask)| Self::is_vertically_symmetric(mask));
+        rule.piece_goals = piece_goals;
+        rule
+    }
+
+    /// Create a new Rule where the given pieces are fixed in place.
+    ///
+    /// Symmetry pairs including a locked piece are not used, since swapping it would move it. Boards are
+    /// only folded left-to-right or top-to-bottom if every locked piece is symmetric that way in place,
+    /// as a horizontal piece in the middle two columns is left-to-right, so mirroring keeps it where it is.
+    ///
+    /// Returns an error if the large piece is locked, since it could never reach the goal.
+    pub fn with_locked(
+        start_board: &Board,
+        goal_mask: &BitPattern,
+        locked: Vec<Piece>,
+    ) -> Result<Self, RuleError> {
+        if locked.contains(&Piece::new(1)) {
+            return Err(RuleError::LockedGoalPiece);
+        }
+        let mut rule = Self::new(start_board, goal_mask);
+        rule.pairs
+            .retain(|(p, q)| !locked.contains(p) && !locked.contains(q));
+        for &piece in &locked {
+            let mask = start_board.pattern.mask_of(piece);
+            rule.horizontal_symmetry &= Self::is_horizontally_symmetric(&mask);
+            rule.vertical_symmetry &= Self::is_vertically_symmetric(&mask);
+        }
+        rule.locked = locked;
+        Ok(rule)
+    }
+
+    /// Moves the piece on the board, wrapping around the edges if `wrap` is set.
+    pub fn move_piece(&self, board: &Board, piece: Piece, direction: Direction) -> Option<Board> {
+        if self.wrap {
+            board.move_piece_wrapping(piece, direction)
+        } else {
+            board.move_piece(piece, direction)
+        }
+    }
+
+    /// Moves the piece covered by the mask like `move_piece`, returning the mask of the moved piece too.
+    ///
+    /// See `Board::move_piece_fast` for the mask required.
+    pub(crate) fn move_piece_fast(
+        &self,
+        board: &Board,
+        piece_mask: &BitPattern,
+        direction: Direction,
+    ) -> Option<(Board, BitPattern)> {
+        if self.wrap {
+            board.move_piece_fast_wrapping(piece_mask, direction)
+        } else {
+            board.move_piece_fast(piece_mask, direction)
         }
     }
 
-    /// Returns true if the board's target piece matches the goal mask.
+    /// Returns every legal move on the board, except for moves of locked pieces.
+    ///
+    /// With `wrap`, the moves across the edges of the board are included, in the same order as `Board::legal_moves`.
+    pub fn legal_moves(&self, board: &Board) -> Vec<(Piece, MovePath, Board)> {
+        if !self.wrap {
+            return board
+                .legal_moves()
+                .into_iter()
+                .filter(|(piece, _, _)| !self.locked.contains(piece))
+                .collect();
+        }
+        let mut moves = vec![];
+        for piece in board.pieces() {
+            if self.locked.contains(&piece) {
+                continue;
+            }
+            let piece_mask = board.pattern.mask_of(piece);
+            for &direction in ALL_DIRECTIONS {
+                let Some((next_board, next_mask)) =
+                    self.move_piece_fast(board, &piece_mask, direction)
+                else {
+                    continue;
+                };
+                moves.push((piece, MovePath::One(direction), next_board.clone()));
+                for &direction2 in ALL_DIRECTIONS {
+                    if direction.reversed() == direction2 {
+                        // Do not move in the opposite direction immediately.
+                        continue;
+                    }
+                    if let Some((next2_board, _)) =
+                        self.move_piece_fast(&next_board, &next_mask, direction2)
+                    {
+                        moves.push((piece, MovePath::Two(direction, direction2), next2_board));
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    /// Returns the piece that must satisfy the goal condition, which is always the large piece #1.
+    pub fn goal_piece(&self) -> Piece {
+        Piece::new(1)
+    }
+
+    /// Returns true if the board's target piece satisfies the goal condition,
+    /// and each of the other goal pieces occupies its mask.
     pub fn is_finished(&self, board: &Board) -> bool {
-        board.pattern.mask_of(Piece::new(1)) == self.goal_mask
+        self.goal
+            .is_satisfied_by(board.pattern.mask_of(self.goal_piece()))
+            && self
+                .piece_goals
+                .iter()
+                .all(|&(piece, mask)| board.pattern.mask_of(piece) == mask)
+    }
+
+    /// Returns true if the board is one of the forbidden boards, compared by its canonical key.
+    pub fn is_forbidden(&self, board: &Board) -> bool {
+        !self.forbidden.is_empty()
+            && self
+                .forbidden
+                .contains(&BoardKey::create(self, board).get_pattern())
+    }
+
+    /// Returns the first move of a shortest solution from the given board.
+    ///
+    /// Returns `None` if the board is already solved or cannot be solved.
+    pub fn hint(&self, board: &Board) -> Option<(Piece, MovePath)> {
+        let path = solve_from(self, board)?;
+        let next_state = path.into_iter().nth(1)?;
+        Some((next_state.piece?, next_state.path))
+    }
+
+    /// Returns the key identifying the puzzle of this rule, shared with its mirror image.
+    ///
+    /// The pairs and the symmetries are left out, since they are derived from the other fields.
+    /// A solution cached for the mirrored rule must be mirrored to be used.
+    pub fn cache_key(&self) -> RuleKey {
+        self.key_of_orientation()
+            .min(self.mirrored().key_of_orientation())
+    }
+
+    /// Returns the key of this rule as it is, without folding its mirror image.
+    fn key_of_orientation(&self) -> RuleKey {
+        let mut piece_goals = self.piece_goals.clone();
+        piece_goals.sort();
+        let mut weights = self.weights.clone().into_iter().collect::<Vec<_>>();
+        weights.sort();
+        let mut locked = self.locked.clone();
+        locked.sort();
+        let mut forbidden = self.forbidden.iter().copied().collect::<Vec<_>>();
+        forbidden.sort();
+        RuleKey {
+            start: BoardKey::create(self, &self.start).get_pattern(),
+            pieces: self.pieces.clone(),
+            goal: self.goal,
+            piece_goals,
+            tabu_window: self.tabu_window,
+            weights,
+            direction_order: self.direction_order.clone(),
+            locked,
+            wrap: self.wrap,
+            allow_reversal: self.allow_reversal,
+            forbidden,
+        }
+    }
+
+    /// Returns the mirror image of this rule, with the starting board, the goals and the forbidden boards mirrored.
+    fn mirrored(&self) -> Rule {
+        let mut mirrored = Rule {
+            start: Board::from_bitpattern(self.start.pattern.mirrored()),
+            goal: self.goal.mirrored(),
+            piece_goals: self
+                .piece_goals
+                .iter()
+                .map(|(piece, mask)| (*piece, mask.mirrored()))
+                .collect(),
+            forbidden: HashSet::new(),
+            ..self.clone()
+        };
+        // The forbidden boards are canonical patterns, so they are made canonical again once mirrored.
+        mirrored.forbidden = self
+            .forbidden
+            .iter()
+            .map(|pattern| {
+                let board = Board::from_bitpattern(pattern.mirrored());
+                BoardKey::create(&mirrored, &board).get_pattern()
+            })
+            .collect();
+        mirrored
+    }
+
+    /// Returns the cost of moving the given piece.
+    pub fn weight_of(&self, piece: Piece) -> usize {
+        self.weights.get(&piece).copied().unwrap_or(1)
+    }
+
+    /// Estimates the number of moves needed to bring the large piece to the goal.
+    ///
+    /// This is the Manhattan distance between the current and the goal positions of the large piece.
+    /// For an avoidance goal, there is no single goal position, so this is always 0.
+    pub fn goal_distance(&self, board: &Board) -> usize {
+        let GoalCondition::Occupies(goal_mask) = self.goal else {
+            return 0;
+        };
+        let current = top_left_cell(&board.pattern.mask_of(self.goal_piece()));
+        let goal = top_left_cell(&goal_mask);
+        match (current, goal) {
+            (Some((row, col)), Some((goal_row, goal_col))) => {
+                row.abs_diff(goal_row) + col.abs_diff(goal_col)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Describes each piece of the starting board with its shape and the (row, column) of its top-left cell.
+    pub fn describe(&self) -> String {
+        self.pieces
+            .iter()
+            .map(|&piece| {
+                let shape = classify(&self.start, piece);
+                let mask = self.start.pattern.mask_of(piece);
+                let (row, col) = top_left_cell(&mask).unwrap_or_default();
+                format!("piece #{piece}: {shape} at ({row}, {col})")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Checks if the two rules are the same puzzle up to symmetry.
+    ///
+    /// Unlike comparing the fields, a rule and its mirror image are the same puzzle.
+    pub fn same_puzzle(&self, other: &Rule) -> bool {
+        self.canonical_form() == other.canonical_form()
+    }
+
+    /// Returns the canonical start pattern and goals among the symmetric variants of the rule.
+    fn canonical_form(&self) -> (BitPattern, GoalCondition, Vec<(Piece, BitPattern)>) {
+        let mut piece_goals = self.piece_goals.clone();
+        piece_goals.sort();
+        let mut mirrored_piece_goals = piece_goals
+            .iter()
+            .map(|&(piece, mask)| (piece, mask.mirrored()))
+            .collect::<Vec<_>>();
+        mirrored_piece_goals.sort();
+
+        if self.goal == self.goal.mirrored() && piece_goals == mirrored_piece_goals {
+            let key = BoardKey::create(self, &self.start).get_pattern();
+            (key, self.goal, piece_goals)
+        } else {
+            // The goals are mirrored along with the board.
+            let pattern = self.start.pattern;
+            (pattern, self.goal, piece_goals).min((
+                pattern.mirrored(),
+                self.goal.mirrored(),
+                mirrored_piece_goals,
+            ))
+        }
+    }
+
+    /// Returns every board where the large piece satisfies the goal condition,
+    /// with the other pieces of the starting board in all arrangements that fit around it.
+    ///
+    /// Pieces of the same shape are distinct, so swapping them yields another board.
+    /// The boards are generated lazily, since there may be a great many of them.
+    pub fn goal_states(&self) -> impl Iterator<Item = Board> + use<> {
+        let other_pieces = self
+            .pieces
+            .iter()
+            .filter(|p| p.id != 1)
+            .map(|p| (p.id, piece_shape(&self.start.pattern, p.id)))
+            .collect::<Vec<_>>();
+        let goal = self.goal;
+        let mut stack = placements(&SHAPE_LARGE)
+            .into_iter()
+            .filter(|&placement| goal.is_satisfied_by(BitPattern::new(placement)))
+            .map(|placement| (placement & 0x1111_1111_1111_1111_1111, 0))
+            .collect::<Vec<_>>();
+
+        // Depth-first search over the placements of each piece in turn.
+        std::iter::from_fn(move || {
+            while let Some((image, index)) = stack.pop() {
+                let Some(&(id, shape)) = other_pieces.get(index) else {
+                    // All pieces are placed.
+                    return Some(Board::new(image));
+                };
+                let id_pattern = id as u128 * 0x1111_1111_1111_1111_1111;
+                for placement in placements(&shape) {
+                    if placement & image == 0 {
+                        stack.push((image | (placement & id_pattern), index + 1));
+                    }
+                }
+            }
+            None
+        })
     }
 
     /// Collect all pieces present in the starting board.
     fn create_pieces(start_board: &Board) -> Vec<Piece> {
-        // Collect all pieces that are present in the starting board.
-        (0x1u8..=0xf)
-            .map(Piece::new)
-            .filter(|&p| start_board.pattern.mask_of(p).is_not_empty())
-            .collect::<Vec<Piece>>()
+        start_board.pieces()
     }
 
     /// Creates pairs of pieces based on their mirrored masks.
@@ -138,33 +583,81 @@ impl Rule {
             .filter(|(p, q)| p < q)
             .collect::<Vec<(_, _)>>()
     }
+
+    /// Checks if the goal mask is symmetric left-to-right.
+    fn is_horizontally_symmetric(goal_mask: &BitPattern) -> bool {
+        *goal_mask == goal_mask.mirrored()
+    }
+
+    /// Checks if the goal mask is symmetric top-to-bottom.
+    fn is_vertically_symmetric(goal_mask: &BitPattern) -> bool {
+        *goal_mask == goal_mask.flipped_vertically()
+    }
+}
+
+impl<'a> IntoIterator for &'a Rule {
+    type Item = &'a Piece;
+    type IntoIter = std::slice::Iter<'a, Piece>;
+
+    /// Iterates over the pieces to move, in the order their moves are explored.
+    fn into_iter(self) -> Self::IntoIter {
+        self.pieces.iter()
+    }
+}
+
+/// Finds the pairs of pieces that correspond to each other when the board is mirrored left-to-right.
+///
+/// Returns an empty list if the goal mask or the board is not mirror-symmetric.
+pub fn symmetry_pairs(board: &Board, goal_mask: &BitPattern) -> Vec<(Piece, Piece)> {
+    let pieces = Rule::create_pieces(board);
+    Rule::create_pairs(board, goal_mask, &pieces)
 }
 
 /// Parses a string representing a 20 hex digit number, allowing for underscores as separators.
-fn parse_20_hex_digits(value: &str) -> Option<BitPattern> {
-    let value = value.trim_start_matches("0x").replace('_', "");
+///
+/// Surrounding whitespace and a single leading `0x` or `0X` prefix are ignored.
+/// Any other non-hex character, including a misplaced prefix, makes the input invalid.
+pub(super) fn parse_20_hex_digits(value: &str) -> Option<BitPattern> {
+    let value = value.trim();
+    let value = match value.get(..2) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("0x") => &value[2..],
+        _ => value,
+    };
+    let value = value.replace('_', "");
+    if !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
     match u128::from_str_radix(&value, 16) {
         Ok(n) if n <= 0xffff_ffff_ffff_ffff_ffff => Some(BitPattern::new(n)),
         _ => None,
     }
 }
 
-/// Returns the shape of the specified piece in the bit pattern.
-fn piece_shape(bit_pattern: &BitPattern, piece_id: u8) -> BitPattern {
-    let piece_mask = bit_pattern.mask_of(Piece::new(piece_id)).get_u128();
-    let piece_shape = match piece_mask {
-        0 => 0,
-        _ => piece_mask >> piece_mask.trailing_zeros(),
-    };
-    BitPattern::new(piece_shape)
+/// Returns the masks of every position where the shape fits within the board.
+fn placements(shape: &BitPattern) -> Vec<u128> {
+    let cells = shape.get_u128().count_ones();
+    let mut placements = vec![];
+    let mut row = *shape;
+    while row.get_u128().count_ones() == cells {
+        let mut placement = row;
+        while placement.get_u128().count_ones() == cells {
+            placements.push(placement.get_u128());
+            placement = placement.moved(Direction::Left);
+        }
+        row = row.moved(Direction::Up);
+    }
+    placements
 }
 
-/// Checks if the given shape is a valid regular piece shape.
-fn is_valid_regular_piece_shapes(shape: &BitPattern) -> bool {
-    matches!(
-        *shape,
-        SHAPE_UNUSED | SHAPE_SMALL | SHAPE_HORIZONTAL | SHAPE_VERTICAL
-    )
+/// Returns the (row, column) of the top-left cell occupied in the bit pattern.
+pub(super) fn top_left_cell(bit_pattern: &BitPattern) -> Option<(usize, usize)> {
+    let value = bit_pattern.get_u128();
+    if value == 0 {
+        return None;
+    }
+    // The 20 cells occupy the lowest 80 bits, from the top-left cell to the bottom-right cell.
+    let index = (value.leading_zeros() as usize - (128 - 80)) / 4;
+    Some((index / 4, index % 4))
 }
 
 /// Counts the number of empty spaces in the given bit pattern.
@@ -180,6 +673,109 @@ fn count_empty_spaces(bit_pattern: &BitPattern) -> usize {
     count
 }
 
+/// The stored form of a `Rule`, with the boards and masks as hex strings accepted by `Rule::parse`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RuleData {
+    start: String,
+    goal: String,
+    /// Whether the large piece must avoid the goal mask, as with `GoalCondition::Avoids`.
+    avoids_goal: bool,
+    piece_goals: Vec<(Piece, String)>,
+    tabu_window: usize,
+    weights: Vec<(Piece, usize)>,
+    direction_order: Vec<Direction>,
+    locked: Vec<Piece>,
+    wrap: bool,
+    allow_reversal: bool,
+    forbidden: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Rule> for RuleData {
+    fn from(rule: Rule) -> Self {
+        let hex = |pattern: &BitPattern| format!("0x{}", pattern.to_hex20());
+        let mut weights = rule.weights.into_iter().collect::<Vec<_>>();
+        weights.sort();
+        let mut forbidden = rule.forbidden.iter().map(hex).collect::<Vec<_>>();
+        forbidden.sort();
+        Self {
+            start: hex(&rule.start.pattern),
+            goal: hex(&rule.goal.mask()),
+            avoids_goal: matches!(rule.goal, GoalCondition::Avoids(_)),
+            piece_goals: rule
+                .piece_goals
+                .iter()
+                .map(|(piece, mask)| (*piece, hex(mask)))
+                .collect(),
+            tabu_window: rule.tabu_window,
+            weights,
+            direction_order: rule.direction_order,
+            locked: rule.locked,
+            wrap: rule.wrap,
+            allow_reversal: rule.allow_reversal,
+            forbidden,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RuleData> for Rule {
+    type Error = RuleError;
+
+    /// Rebuilds the rule with `with_piece_goals` and `with_locked`, validating the boards as `parse` does.
+    ///
+    /// The region of `GoalCondition::Avoids` may have any shape, so only its format is checked.
+    fn try_from(data: RuleData) -> Result<Self, Self::Error> {
+        let start = Self::parse_start_board(&data.start)?;
+        let (goal_mask, goal) = match data.avoids_goal {
+            true => {
+                let goal_mask =
+                    parse_20_hex_digits(&data.goal).ok_or(RuleError::InvalidGoalMaskHexLength)?;
+                (goal_mask, GoalCondition::Avoids(goal_mask))
+            }
+            false => {
+                let goal_mask = Self::parse_goal_mask(&data.goal)?;
+                (goal_mask, GoalCondition::Occupies(goal_mask))
+            }
+        };
+        let piece_goals = data
+            .piece_goals
+            .iter()
+            .map(|(piece, mask)| Some((*piece, parse_20_hex_digits(mask)?)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(RuleError::InvalidGoalMaskHexLength)?;
+        let forbidden = data
+            .forbidden
+            .iter()
+            .map(|key| parse_20_hex_digits(key))
+            .collect::<Option<HashSet<_>>>()
+            .ok_or(RuleError::InvalidForbiddenHexLength)?;
+
+        let locked_rule = Self::with_locked(&start, &goal_mask, data.locked)?;
+        let mut rule = if piece_goals.is_empty() {
+            locked_rule
+        } else {
+            // Symmetry pairs are already dropped for the piece goals, so only the locked pieces remain to apply.
+            let rule = Self::with_piece_goals(&start, &goal_mask, piece_goals);
+            Self {
+                horizontal_symmetry: rule.horizontal_symmetry && locked_rule.horizontal_symmetry,
+                vertical_symmetry: rule.vertical_symmetry && locked_rule.vertical_symmetry,
+                locked: locked_rule.locked,
+                ..rule
+            }
+        };
+        rule.goal = goal;
+        rule.tabu_window = data.tabu_window;
+        rule.weights = data.weights.into_iter().collect();
+        rule.direction_order = data.direction_order;
+        rule.wrap = data.wrap;
+        rule.allow_reversal = data.allow_reversal;
+        rule.forbidden = forbidden;
+        Ok(rule)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -251,6 +847,28 @@ mod tests {
         assert!(rule.is_finished(&Board::new(0x2003_2783_4455_6119_6119)));
     }
 
+    #[test]
+    fn rule_clone_should_copy_fields() {
+        // Arrange
+        let mut rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        rule.weights.insert(Piece::new(1), 3);
+
+        // Act
+        let cloned = rule.clone();
+
+        // Assert
+        assert_eq!(cloned.start, rule.start);
+        assert_eq!(cloned.pieces, rule.pieces);
+        assert_eq!(cloned.pairs, rule.pairs);
+        assert_eq!(cloned.goal, rule.goal);
+        assert_eq!(cloned.tabu_window, rule.tabu_window);
+        assert_eq!(cloned.vertical_symmetry, rule.vertical_symmetry);
+        assert_eq!(cloned.weights, rule.weights);
+    }
+
     #[test]
     fn rule_new_should_handle_asymmetric_goal() {
         // Arrange & Act
@@ -318,6 +936,228 @@ mod tests {
         assert_eq!(pairs, vec![]);
     }
 
+    #[test]
+    fn symmetry_pairs_should_return_mirrored_pieces() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        // Act
+        let mut pairs = symmetry_pairs(&board, &goal_mask);
+        // Assert
+        pairs.sort();
+        let expected_pairs = vec![
+            (Piece::new(2), Piece::new(3)),
+            (Piece::new(4), Piece::new(6)),
+            (Piece::new(7), Piece::new(8)),
+            (Piece::new(9), Piece::new(10)),
+        ];
+        assert_eq!(pairs, expected_pairs);
+    }
+
+    #[test]
+    fn symmetry_pairs_should_return_empty_for_asymmetric_goal() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+        let asymmetric_goal_mask = BitPattern::new(0x0000_0000_0000_00ff_00ff);
+        // Act & Assert
+        assert_eq!(symmetry_pairs(&board, &asymmetric_goal_mask), vec![]);
+    }
+
+    #[test]
+    fn symmetry_pairs_should_return_empty_for_asymmetric_board() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_7896_700a);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        // Act & Assert
+        assert_eq!(symmetry_pairs(&board, &goal_mask), vec![]);
+    }
+
+    #[test]
+    fn rule_new_should_detect_vertical_symmetry() {
+        // Arrange & Act
+        let symmetric_rule = Rule::with_goal(
+            &Board::new(0x2455_2400_6811_6311_739a),
+            GoalCondition::Avoids(BitPattern::new(0x0000_0000_ffff_0000_0000)),
+        );
+        let asymmetric_rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+
+        // Assert
+        assert!(symmetric_rule.vertical_symmetry);
+        assert!(!asymmetric_rule.vertical_symmetry);
+    }
+
+    #[test]
+    fn is_finished_should_check_avoidance_goal() {
+        // Arrange
+        let rule = Rule::with_goal(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            GoalCondition::Avoids(BitPattern::new(0x0000_0000_0000_0ff0_0ff0)),
+        );
+
+        // Act & Assert
+        assert!(rule.is_finished(&Board::new(0x2113_2113_4556_4786_900a)));
+        assert!(!rule.is_finished(&Board::new(0x2003_2783_4556_4116_9a11)));
+        assert_eq!(rule.goal_distance(&rule.start), 0);
+    }
+
+    #[test]
+    fn is_finished_should_check_all_piece_goals() {
+        // Arrange: The horizontal piece #4 must also be at the top center
+        let rule = Rule::with_piece_goals(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+            vec![(Piece::new(4), BitPattern::new(0x0ff0_0000_0000_0000_0000))],
+        );
+
+        // Act & Assert
+        assert!(rule.pairs.is_empty());
+        assert!(rule.is_finished(&Board::new(0x2443_2553_0000_0110_0110)));
+        assert!(!rule.is_finished(&Board::new(0x2553_2443_0000_0110_0110)));
+        assert!(!rule.is_finished(&Board::new(0x2443_2113_0110_5500_0000)));
+    }
+
+    #[test]
+    fn with_locked_should_skip_locked_pieces() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_0000_7008);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+
+        // Act
+        let rule = Rule::with_locked(&start, &goal_mask, vec![Piece::new(2)]).unwrap();
+
+        // Assert
+        assert!(
+            rule.pairs
+                .iter()
+                .all(|&(p, q)| p != Piece::new(2) && q != Piece::new(2))
+        );
+        assert!(!rule.horizontal_symmetry);
+        assert!(!rule.vertical_symmetry);
+        assert!(!rule.legal_moves(&start).is_empty());
+        assert!(
+            rule.legal_moves(&start)
+                .iter()
+                .all(|(piece, _, _)| *piece != Piece::new(2))
+        );
+        assert_eq!(
+            Rule::with_locked(&start, &goal_mask, vec![Piece::new(1)]).err(),
+            Some(RuleError::LockedGoalPiece)
+        );
+    }
+
+    #[test]
+    fn with_locked_should_keep_symmetry_of_symmetric_pieces() {
+        // Arrange: Piece #5 is horizontal in the middle two columns, and piece #7 is on the left
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+
+        // Act
+        let centered = Rule::with_locked(&start, &goal_mask, vec![Piece::new(5)]).unwrap();
+        let off_center = Rule::with_locked(&start, &goal_mask, vec![Piece::new(7)]).unwrap();
+
+        // Assert
+        assert!(centered.horizontal_symmetry);
+        assert!(!off_center.horizontal_symmetry);
+        assert!(
+            off_center
+                .pairs
+                .iter()
+                .all(|&(p, q)| p != Piece::new(7) && q != Piece::new(7))
+        );
+    }
+
+    #[test]
+    fn describe_should_list_pieces() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        // Act & Assert
+        assert_eq!(
+            rule.describe(),
+            [
+                "piece #1: Large at (0, 1)",
+                "piece #2: Vertical at (0, 0)",
+                "piece #3: Vertical at (0, 3)",
+                "piece #4: Vertical at (2, 0)",
+                "piece #5: Horizontal at (2, 1)",
+                "piece #6: Vertical at (2, 3)",
+                "piece #7: Small at (3, 1)",
+                "piece #8: Small at (3, 2)",
+                "piece #9: Small at (4, 0)",
+                "piece #a: Small at (4, 3)",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn same_puzzle_should_ignore_mirroring() {
+        // Arrange
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let rule = Rule::new(&Board::new(0x2113_2113_4556_4786_900a), &goal_mask);
+        let mirrored_rule = Rule::new(&Board::new(0x3112_3112_6554_6874_a009), &goal_mask);
+        let other_rule = Rule::new(&Board::new(0x2113_2113_4556_7896_700a), &goal_mask);
+        let other_goal_rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0ff0_0ff0_0000),
+        );
+
+        // Act & Assert
+        assert!(rule.same_puzzle(&rule));
+        assert!(rule.same_puzzle(&mirrored_rule));
+        assert!(mirrored_rule.same_puzzle(&rule));
+        assert!(!rule.same_puzzle(&other_rule));
+        assert!(!rule.same_puzzle(&other_goal_rule));
+    }
+
+    #[test]
+    fn same_puzzle_should_mirror_asymmetric_goal() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_ff00_ff00),
+        );
+        let mirrored_rule = Rule::new(
+            &Board::new(0x3112_3112_6554_6874_a009),
+            &BitPattern::new(0x0000_0000_0000_00ff_00ff),
+        );
+        let mirrored_start_rule = Rule::new(
+            &Board::new(0x3112_3112_6554_6874_a009),
+            &BitPattern::new(0x0000_0000_0000_ff00_ff00),
+        );
+
+        // Act & Assert
+        assert!(rule.same_puzzle(&mirrored_rule));
+        assert!(!rule.same_puzzle(&mirrored_start_rule));
+    }
+
+    #[test]
+    fn goal_states_should_yield_finished_boards() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+
+        // Act
+        let boards = rule.goal_states().collect::<Vec<_>>();
+
+        // Assert
+        assert!(boards.iter().all(|board| rule.is_finished(board)));
+        assert!(boards.iter().all(|board| board.pieces() == rule.pieces));
+        assert!(boards.contains(&Board::new(0x2443_2553_0000_0110_0110)));
+        let unique_boards = boards
+            .iter()
+            .map(|board| board.pattern)
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(unique_boards.len(), boards.len());
+    }
+
     #[test]
     fn test_parse_20_hex_digits_valid() {
         // Arrange: Valid 20-digit hex string with underscores
@@ -339,6 +1179,79 @@ mod tests {
         let s = "0x1234_5678_9abc_defg_1234";
         // Act & Assert
         assert_eq!(parse_20_hex_digits(s), None);
+
+        // Arrange: Invalid: Embedded prefix
+        let s = "0x1234_0x5678_9abc_def0";
+        // Act & Assert
+        assert_eq!(parse_20_hex_digits(s), None);
+
+        // Arrange: Invalid: Repeated prefix
+        let s = "0x0x1234_5678_9abc_def0_1234";
+        // Act & Assert
+        assert_eq!(parse_20_hex_digits(s), None);
+
+        // Arrange: Invalid: Sign
+        let s = "+1234_5678_9abc_def0_1234";
+        // Act & Assert
+        assert_eq!(parse_20_hex_digits(s), None);
+    }
+
+    #[test]
+    fn test_parse_20_hex_digits_pathological() {
+        // Arrange: Strings that must be rejected without panicking
+        let huge = "f".repeat(100_000);
+        let zeros = format!("{}1", "0".repeat(100_000));
+        let cases = [
+            ("", None),
+            ("0", Some(BitPattern::new(0))),
+            ("0x", None),
+            ("0X_", None),
+            ("x", None),
+            ("_", None),
+            ("____________________", None),
+            ("é", None),
+            ("0é", None),
+            ("0xé", None),
+            ("０ｘ１２３４", None),
+            ("\u{0}", None),
+            ("0x1234_5678_9abc_def0_123\u{301}", None),
+            ("-0", None),
+            (huge.as_str(), None),
+            (zeros.as_str(), Some(BitPattern::new(1))),
+        ];
+
+        for (s, expected) in cases {
+            // Act & Assert
+            assert_eq!(parse_20_hex_digits(s), expected, "{s:?}");
+            assert!(Rule::parse(s, s).is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_20_hex_digits_prefix_and_whitespace() {
+        // Arrange: Uppercase prefix
+        let s = "0X1234_5678_9abc_def0_1234";
+        // Act & Assert
+        assert_eq!(
+            parse_20_hex_digits(s),
+            Some(BitPattern::new(0x1234_5678_9abc_def0_1234))
+        );
+
+        // Arrange: Leading and trailing spaces
+        let s = "  0x1234_5678_9abc_def0_1234\t ";
+        // Act & Assert
+        assert_eq!(
+            parse_20_hex_digits(s),
+            Some(BitPattern::new(0x1234_5678_9abc_def0_1234))
+        );
+
+        // Arrange: No prefix
+        let s = "1234_5678_9abc_def0_1234";
+        // Act & Assert
+        assert_eq!(
+            parse_20_hex_digits(s),
+            Some(BitPattern::new(0x1234_5678_9abc_def0_1234))
+        );
     }
 
     #[test]
@@ -360,30 +1273,345 @@ mod tests {
     }
 
     #[test]
-    fn test_piece_shape_and_is_valid_regular_piece_shapes() {
-        // Arrange: Small piece
-        let pat = BitPattern::new(0x0000_000f);
+    fn hint_should_return_first_move_of_solution() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let board = Board::new(0x2443_2783_5566_9011_a011);
+
+        // Act
+        let hint = rule.hint(&board);
+
+        // Assert
+        assert_eq!(hint, Some((Piece::new(1), MovePath::One(Direction::Left))));
+    }
+
+    #[test]
+    fn hint_should_return_none_for_solved_board() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let solved_board = Board::new(0x2003_2783_4455_6119_6119);
+
         // Act & Assert
-        assert!(is_valid_regular_piece_shapes(&pat));
+        assert_eq!(rule.hint(&solved_board), None);
+    }
+
+    #[test]
+    fn weight_of_should_default_to_one() {
+        // Arrange
+        let mut rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        rule.weights.insert(Piece::new(2), 5);
 
-        // Arrange: Horizontal piece
-        let pat = BitPattern::new(0x0000_00ff);
         // Act & Assert
-        assert!(is_valid_regular_piece_shapes(&pat));
+        assert_eq!(rule.weight_of(Piece::new(1)), 1);
+        assert_eq!(rule.weight_of(Piece::new(2)), 5);
+    }
+
+    #[test]
+    fn goal_distance_should_return_manhattan_distance() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
 
-        // Arrange: Vertical piece
-        let pat = BitPattern::new(0x000f_000f);
         // Act & Assert
-        assert!(is_valid_regular_piece_shapes(&pat));
+        assert_eq!(rule.goal_distance(&rule.start), 3);
+        assert_eq!(
+            rule.goal_distance(&Board::new(0x2003_2783_4455_6911_6011)),
+            1
+        );
+        assert_eq!(
+            rule.goal_distance(&Board::new(0x2003_2783_4455_6119_6119)),
+            0
+        );
+    }
 
-        // Arrange: Unused
-        let pat = BitPattern::new(0x0000_0000);
+    #[test]
+    fn test_top_left_cell() {
         // Act & Assert
-        assert!(is_valid_regular_piece_shapes(&pat));
+        assert_eq!(
+            top_left_cell(&BitPattern::new(0xff00_ff00_0000_0000_0000)),
+            Some((0, 0))
+        );
+        assert_eq!(
+            top_left_cell(&BitPattern::new(0x0000_0000_0000_00ff_00ff)),
+            Some((3, 2))
+        );
+        assert_eq!(
+            top_left_cell(&BitPattern::new(0x0000_0000_0000_0000_000f)),
+            Some((4, 3))
+        );
+        assert_eq!(top_left_cell(&BitPattern::new(0)), None);
+    }
+
+    #[test]
+    fn test_legal_moves_with_wrap() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let mut rule = Rule::new(&start, &BitPattern::new(0x0000_0000_0000_0ff0_0ff0));
+        let plain_moves = rule.legal_moves(&start);
+        rule.wrap = true;
+
+        // Act
+        let moves = rule.legal_moves(&start);
+
+        // Assert
+        assert_eq!(plain_moves, start.legal_moves());
+        assert!(plain_moves.iter().all(|m| moves.contains(m)));
+        assert!(moves.contains(&(
+            Piece::new(1),
+            MovePath::One(Direction::Up),
+            Board::new(0x2113_2003_4556_4786_911a)
+        )));
+        for (piece, path, board) in &moves {
+            let replayed = path
+                .directions()
+                .into_iter()
+                .try_fold(start.clone(), |b, d| b.move_piece_wrapping(*piece, d));
+            assert_eq!(replayed.as_ref(), Some(board));
+        }
+    }
+
+    #[test]
+    fn test_cache_key() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0xff00_ff00_0000_0000_0000),
+        );
+        let mirrored = Rule::new(
+            &Board::new(0x3112_3112_6554_6874_a009),
+            &BitPattern::new(0x00ff_00ff_0000_0000_0000),
+        );
+        let other_goal = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x00ff_00ff_0000_0000_0000),
+        );
+
+        // Act & Assert
+        assert_eq!(rule.cache_key(), mirrored.cache_key());
+        assert_ne!(rule.cache_key(), other_goal.cache_key());
+        let cache = HashMap::from([(rule.cache_key(), 7)]);
+        assert_eq!(cache.get(&mirrored.cache_key()), Some(&7));
+    }
+
+    #[test]
+    fn test_cache_key_with_options() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let rule = Rule::new(&start, &goal_mask);
+        let locked = Rule {
+            locked: vec![Piece::new(6)],
+            ..rule.clone()
+        };
+        let wrap = Rule {
+            wrap: true,
+            ..rule.clone()
+        };
+        let tabu = Rule {
+            tabu_window: 2,
+            ..rule.clone()
+        };
+        let reversal = Rule {
+            allow_reversal: true,
+            ..rule.clone()
+        };
+        let forbidden = Rule {
+            forbidden: HashSet::from([BoardKey::create(&rule, &start).get_pattern()]),
+            ..rule.clone()
+        };
+        let piece_goals = Rule::with_piece_goals(
+            &start,
+            &goal_mask,
+            vec![(Piece::new(2), BitPattern::new(0xf000_f000_0000_0000_0000))],
+        );
+        // The mirrored starting board, which the symmetric goal folds onto the same puzzle.
+        let mirrored_start = Rule::new(&Board::new(0x3112_3112_6554_6874_a009), &goal_mask);
+
+        // Act & Assert
+        for other in [&locked, &wrap, &tabu, &reversal, &forbidden, &piece_goals] {
+            assert_ne!(rule.cache_key(), other.cache_key());
+        }
+        assert_eq!(rule.cache_key(), mirrored_start.cache_key());
+        assert_eq!(rule.cache_key(), rule.clone().cache_key());
+    }
+
+    #[test]
+    fn test_into_iter() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+
+        // Act
+        let mut ids = vec![];
+        for piece in &rule {
+            ids.push(piece.to_string());
+        }
+
+        // Assert
+        assert_eq!(ids, ["1", "2", "3", "4", "5", "6", "7", "8", "9", "a"]);
+    }
+
+    #[test]
+    fn test_goal_piece_highlighted() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let options = super::super::BoardDisplayOptions {
+            highlight: Some(rule.goal_piece()),
+            ..Default::default()
+        };
+
+        // Act
+        let rendered = rule.start.render(&options);
+
+        // Assert
+        assert_eq!(rule.goal_piece(), Piece::new(1));
+        assert_eq!(rendered.matches('#').count(), 4);
+        assert!(!rendered.contains('1'));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        // Arrange
+        let mut rule = Rule::with_locked(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+            vec![Piece::new(9)],
+        )
+        .unwrap();
+        rule.tabu_window = 2;
+        rule.weights.insert(Piece::new(1), 3);
+        rule.direction_order = vec![Direction::Down, Direction::Up];
+        rule.wrap = true;
+        rule.forbidden
+            .insert(BitPattern::new(0x2113_2113_4556_4786_90a0));
+
+        // Act
+        let restored = Rule::try_from(RuleData::from(rule.clone())).unwrap();
+
+        // Assert
+        assert!(restored.same_puzzle(&rule));
+        assert_eq!(restored.pieces, rule.pieces);
+        let sorted = |pairs: &[(Piece, Piece)]| {
+            let mut pairs = pairs.to_vec();
+            pairs.sort();
+            pairs
+        };
+        assert_eq!(sorted(&restored.pairs), sorted(&rule.pairs));
+        assert_eq!(restored.vertical_symmetry, rule.vertical_symmetry);
+        assert_eq!(restored.tabu_window, 2);
+        assert_eq!(restored.weights, rule.weights);
+        assert_eq!(restored.direction_order, rule.direction_order);
+        assert_eq!(restored.locked, rule.locked);
+        assert!(restored.wrap);
+        assert!(!restored.allow_reversal);
+        assert_eq!(restored.forbidden, rule.forbidden);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_validates_like_parse() {
+        // Arrange
+        let data = || {
+            RuleData::from(Rule::new(
+                &Board::new(0x2113_2113_4556_4786_900a),
+                &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+            ))
+        };
+        let mut too_many_empties = data();
+        too_many_empties.start = "0x2113_2113_4556_4786_000a".to_string();
+        let mut invalid_goal = data();
+        invalid_goal.goal = "0x0000_0000_0000_0000_ffff".to_string();
+        let mut locked_goal_piece = data();
+        locked_goal_piece.locked = vec![Piece::new(1)];
+        let mut avoided_row = data();
+        avoided_row.goal = "0x0000_0000_ffff_0000_0000".to_string();
+        avoided_row.avoids_goal = true;
 
-        // Arrange: Invalid shape
-        let pat = BitPattern::new(0x0000_0fff);
         // Act & Assert
-        assert!(!is_valid_regular_piece_shapes(&pat));
+        assert_eq!(
+            Rule::try_from(too_many_empties).err(),
+            Some(RuleError::StartBoardInvalidEmptyCount {
+                found: 3,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            Rule::try_from(invalid_goal).err(),
+            Some(RuleError::GoalMaskShapeError)
+        );
+        assert_eq!(
+            Rule::try_from(locked_goal_piece).err(),
+            Some(RuleError::LockedGoalPiece)
+        );
+        assert_eq!(
+            Rule::try_from(avoided_row).unwrap().goal,
+            GoalCondition::Avoids(BitPattern::new(0x0000_0000_ffff_0000_0000))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip_with_piece_goals() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let rule = Rule::with_piece_goals(
+            &start,
+            &goal_mask,
+            vec![(Piece::new(5), BitPattern::new(0xff00_0000_0000_0000_0000))],
+        );
+
+        // Act
+        let restored = Rule::try_from(RuleData::from(rule.clone())).unwrap();
+
+        // Assert
+        assert!(restored.same_puzzle(&rule));
+        assert_eq!(restored.pairs, vec![]);
+        assert_eq!(restored.horizontal_symmetry, rule.horizontal_symmetry);
+        assert_eq!(restored.vertical_symmetry, rule.vertical_symmetry);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_invalid_hex() {
+        // Arrange
+        let mut data = RuleData::from(Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        ));
+        data.start = "0xé".to_string();
+
+        let mut forbidden_data = RuleData::from(Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        ));
+        forbidden_data.forbidden = vec!["0xé".to_string()];
+
+        // Act & Assert
+        assert_eq!(
+            Rule::try_from(data).err(),
+            Some(RuleError::InvalidStartBoardHexLength)
+        );
+        assert_eq!(
+            Rule::try_from(forbidden_data).err(),
+            Some(RuleError::InvalidForbiddenHexLength)
+        );
     }
 }
@@ -0,0 +1,47 @@
+/// Statistics collected while searching for a solution.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct SearchStats {
+    /// The deepest search depth reached.
+    pub max_depth: usize,
+    /// The total number of distinct states visited.
+    pub visited_states: usize,
+}
+
+impl SearchStats {
+    /// Records a visit to a new state at the given depth.
+    pub fn record(&mut self, depth: usize) {
+        self.max_depth = self.max_depth.max(depth);
+        self.visited_states += 1;
+    }
+}
+
+impl std::fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "explored {} states up to depth {}",
+            self.visited_states, self.max_depth
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_stats_record() {
+        // Arrange
+        let mut stats = SearchStats::default();
+
+        // Act
+        stats.record(0);
+        stats.record(1);
+        stats.record(1);
+
+        // Assert
+        assert_eq!(stats.max_depth, 1);
+        assert_eq!(stats.visited_states, 3);
+        assert_eq!(format!("{stats}"), "explored 3 states up to depth 1");
+    }
+}
@@ -0,0 +1,167 @@
+use super::BitPattern;
+use super::Board;
+use super::Piece;
+
+/// The shape representing a space without a piece.
+pub(crate) const SHAPE_UNUSED: BitPattern = BitPattern::new(0x0000_0000);
+/// The shape of a small piece, which occupies a single cell in the puzzle.
+pub(crate) const SHAPE_SMALL: BitPattern = BitPattern::new(0x0000_000f);
+/// The shape of a horizontally elongated piece, which occupies two columns in the puzzle.
+pub(crate) const SHAPE_HORIZONTAL: BitPattern = BitPattern::new(0x0000_00ff);
+/// The shape of a vertically elongated piece, which occupies two rows in the puzzle.
+pub(crate) const SHAPE_VERTICAL: BitPattern = BitPattern::new(0x000f_000f);
+/// The shape of the large piece to be moved to the goal.
+pub(crate) const SHAPE_LARGE: BitPattern = BitPattern::new(0x00ff_00ff);
+
+/// The shape class of a piece.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Shape {
+    /// The piece is not on the board.
+    Unused,
+    /// A single cell.
+    Small,
+    /// Two cells side by side.
+    Horizontal,
+    /// Two cells one above the other.
+    Vertical,
+    /// Two by two cells, the shape of the piece to be moved to the goal.
+    Large,
+    /// Any other shape, which the solver does not support.
+    Irregular,
+}
+
+impl Shape {
+    /// Checks if the shape is allowed for pieces other than the large piece.
+    pub fn is_regular(&self) -> bool {
+        matches!(
+            self,
+            Shape::Unused | Shape::Small | Shape::Horizontal | Shape::Vertical
+        )
+    }
+}
+
+impl std::fmt::Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Shape::Unused => "Unused",
+            Shape::Small => "Small",
+            Shape::Horizontal => "Horizontal",
+            Shape::Vertical => "Vertical",
+            Shape::Large => "Large",
+            Shape::Irregular => "Irregular",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// Classifies the shape of the piece on the board.
+pub fn classify(board: &Board, piece: Piece) -> Shape {
+    match piece_shape(&board.pattern, piece.id) {
+        SHAPE_UNUSED => Shape::Unused,
+        SHAPE_SMALL => Shape::Small,
+        SHAPE_HORIZONTAL => Shape::Horizontal,
+        SHAPE_VERTICAL => Shape::Vertical,
+        SHAPE_LARGE => Shape::Large,
+        _ => Shape::Irregular,
+    }
+}
+
+/// Builds the mask of the cells a piece of the shape covers with its top-left cell at the given row and column.
+///
+/// Returns `None` if the shape is not a piece shape, or does not fit within the board there.
+pub fn goal_mask_at(row: usize, col: usize, shape: Shape) -> Option<BitPattern> {
+    let (pattern, height, width) = match shape {
+        Shape::Small => (SHAPE_SMALL, 1, 1),
+        Shape::Horizontal => (SHAPE_HORIZONTAL, 1, 2),
+        Shape::Vertical => (SHAPE_VERTICAL, 2, 1),
+        Shape::Large => (SHAPE_LARGE, 2, 2),
+        Shape::Unused | Shape::Irregular => return None,
+    };
+    if row > 5 - height || col > 4 - width {
+        return None;
+    }
+    // The shapes have their bottom-right cell at the bottom-right of the board.
+    let cells_from_end = (5 - row - height) * 4 + (4 - col - width);
+    Some(BitPattern::new(pattern.get_u128() << (cells_from_end * 4)))
+}
+
+/// Returns the shape of the specified piece in the bit pattern.
+pub(crate) fn piece_shape(bit_pattern: &BitPattern, piece_id: u8) -> BitPattern {
+    let piece_mask = bit_pattern.mask_of(Piece::new(piece_id)).get_u128();
+    let piece_shape = match piece_mask {
+        0 => 0,
+        _ => piece_mask >> piece_mask.trailing_zeros(),
+    };
+    BitPattern::new(piece_shape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        // Arrange
+        let board = Board::new(0x2113_2113_4556_4786_900a);
+
+        // Act & Assert
+        assert_eq!(classify(&board, Piece::new(1)), Shape::Large);
+        assert_eq!(classify(&board, Piece::new(2)), Shape::Vertical);
+        assert_eq!(classify(&board, Piece::new(5)), Shape::Horizontal);
+        assert_eq!(classify(&board, Piece::new(7)), Shape::Small);
+        assert_eq!(classify(&board, Piece::new(0xb)), Shape::Unused);
+        assert_eq!(
+            classify(&Board::new(0x2113_2113_4556_4786_700a), Piece::new(7)),
+            Shape::Irregular
+        );
+    }
+
+    #[test]
+    fn test_is_regular() {
+        // Act & Assert
+        assert!(Shape::Unused.is_regular());
+        assert!(Shape::Small.is_regular());
+        assert!(Shape::Horizontal.is_regular());
+        assert!(Shape::Vertical.is_regular());
+        assert!(!Shape::Large.is_regular());
+        assert!(!Shape::Irregular.is_regular());
+    }
+
+    #[test]
+    fn test_piece_shape() {
+        // Arrange
+        let pattern = BitPattern::new(0x2113_2113_4556_4786_900a);
+
+        // Act & Assert
+        assert_eq!(piece_shape(&pattern, 1), SHAPE_LARGE);
+        assert_eq!(piece_shape(&pattern, 4), SHAPE_VERTICAL);
+        assert_eq!(piece_shape(&pattern, 5), SHAPE_HORIZONTAL);
+        assert_eq!(piece_shape(&pattern, 0xa), SHAPE_SMALL);
+        assert_eq!(piece_shape(&pattern, 0xb), SHAPE_UNUSED);
+    }
+
+    #[test]
+    fn test_goal_mask_at() {
+        // Act & Assert
+        assert_eq!(
+            goal_mask_at(3, 1, Shape::Large),
+            Some(BitPattern::new(0x0000_0000_0000_0ff0_0ff0))
+        );
+        assert_eq!(
+            goal_mask_at(0, 0, Shape::Large),
+            Some(BitPattern::new(0xff00_ff00_0000_0000_0000))
+        );
+        assert_eq!(
+            goal_mask_at(4, 3, Shape::Small),
+            Some(BitPattern::new(0x0000_0000_0000_0000_000f))
+        );
+        assert_eq!(
+            goal_mask_at(1, 2, Shape::Horizontal),
+            Some(BitPattern::new(0x0000_00ff_0000_0000_0000))
+        );
+        assert_eq!(goal_mask_at(4, 1, Shape::Large), None);
+        assert_eq!(goal_mask_at(0, 3, Shape::Horizontal), None);
+        assert_eq!(goal_mask_at(0, 0, Shape::Irregular), None);
+        assert_eq!(goal_mask_at(usize::MAX, usize::MAX, Shape::Small), None);
+    }
+}
@@ -1,72 +1,673 @@
 use super::BitPattern;
 use super::Board;
 use super::Direction;
+use super::Heuristic;
 use super::MovePath;
+use super::Piece;
+use super::RecentPieces;
 use super::Rule;
+use super::SearchStats;
+use super::SolveOutcome;
 use super::State;
 use super::VisitedHistory;
+use super::opening_book;
+use crate::astar;
 use crate::bfs;
-
-/// All possible directions for moving pieces in the puzzle.
-static ALL_DIRECTIONS: &[Direction] = &[
-    Direction::Up,
-    Direction::Down,
-    Direction::Left,
-    Direction::Right,
-];
+use crate::bfs::SearchOutcome;
+use crate::dijkstra;
+use crate::greedy;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+use std::time::Duration;
 
 /// Solves the klotski puzzle using a breadth-first search algorithm.
+///
+/// The default puzzle is answered from a precomputed solution, without searching.
+///
+/// With a tabu window of at most one move, the path never passes through two boards folded into the same
+/// `BoardKey`, such as a board and its mirror image. Past the first of them, the rest of the path could be
+/// mirrored or relabeled to reach the goal from the earlier one, so the path would not be the shortest.
 pub fn solve(rule: &Rule) -> Option<Vec<State>> {
-    let start_state = State {
-        board: rule.start.clone(),
-        piece: None,
-        path: MovePath::None,
+    if let Some(path) = opening_book::lookup(rule) {
+        log_solution(&path);
+        return Some(path);
+    }
+    let (path, _stats) = solve_with_stats(rule);
+    path
+}
+
+/// Solves the klotski puzzle like `solve`, also returning statistics about the search.
+///
+/// The statistics are returned even when no solution is found.
+pub fn solve_with_stats(rule: &Rule) -> (Option<Vec<State>>, SearchStats) {
+    let (path, stats) = search_from(rule, &rule.start);
+    if let Some(path) = &path {
+        log_solution(path);
+    }
+    (path, stats)
+}
+
+/// Solves the klotski puzzle `n` times, to measure the amortized cost of solving.
+///
+/// The visited history and the search queue are cleared and reused between the runs,
+/// so only the first run pays for growing them.
+///
+/// Returns the last solution, and the statistics averaged over the runs.
+pub fn solve_n_times(rule: &Rule, n: usize) -> (Option<Vec<State>>, SearchStats) {
+    let start_state = State::new(&rule.start);
+
+    let is_goal = |s: &State| rule.is_finished(&s.board);
+    let neighbors = |s: &State| get_neighbors(rule, s);
+
+    let mut visited = new_visited(rule);
+    let mut finder = bfs::PathFinder::new();
+    let mut path = None;
+    let mut total = SearchStats::default();
+    for _ in 0..n {
+        visited.clear();
+        let mut stats = SearchStats::default();
+        let try_visit = |s: &State, depth: usize| {
+            let is_new = visited.try_visit(visit_key(rule, s), depth);
+            if is_new {
+                stats.record(depth);
+            }
+            is_new
+        };
+        path = finder.find_path(&start_state, is_goal, neighbors, try_visit);
+        total.max_depth += stats.max_depth;
+        total.visited_states += stats.visited_states;
+    }
+
+    let average = SearchStats {
+        max_depth: total.max_depth / n.max(1),
+        visited_states: total.visited_states / n.max(1),
+    };
+    (path, average)
+}
+
+/// The number of moves shown in the summary logged when a solution is found.
+const LOGGED_MOVES: usize = 5;
+
+/// Logs a one-line summary of the solution at the info level: the move count and the first few moves.
+fn log_solution(path: &[State]) {
+    if !log::log_enabled!(log::Level::Info) {
+        return;
+    }
+    let moves = path
+        .iter()
+        .filter_map(|s| Some(format!("#{} {}", s.piece?, s.path)))
+        .collect::<Vec<_>>();
+    let ellipsis = if moves.len() > LOGGED_MOVES {
+        ", ..."
+    } else {
+        ""
+    };
+    log::info!(
+        "Solved in {} moves: {}{ellipsis}",
+        moves.len(),
+        moves[..moves.len().min(LOGGED_MOVES)].join(", ")
+    );
+}
+
+/// Solves the klotski puzzle starting from the given board instead of the rule's starting board.
+pub fn solve_from(rule: &Rule, board: &Board) -> Option<Vec<State>> {
+    let (path, _stats) = search_from(rule, board);
+    path
+}
+
+/// Solves the klotski puzzle with a solution that begins with the given moves, such as those already played.
+///
+/// The moves are replayed from the rule's starting board, then a shortest path is searched from the
+/// resulting board. The returned path includes the replayed moves.
+///
+/// Returns `None` if a move of the prefix is illegal or moves a locked piece, or if the puzzle cannot be
+/// solved after the prefix.
+pub fn solve_from_prefix(rule: &Rule, prefix: &[(Piece, MovePath)]) -> Option<Vec<State>> {
+    let mut path = vec![State::new(&rule.start)];
+    for (piece, move_path) in prefix {
+        let current = path.last()?;
+        let next_state = State {
+            board: current.board.clone(),
+            piece: Some(*piece),
+            path: move_path.clone(),
+            earlier_pieces: next_earlier_pieces(rule, current),
+            empties: current.empties,
+        };
+        let board = apply_step(rule, &current.board, &next_state)?;
+        path.push(State {
+            empties: board.empty_mask(),
+            board,
+            ..next_state
+        });
+    }
+
+    let rest = solve_from(rule, &path.last()?.board)?;
+    path.extend(rest.into_iter().skip(1));
+    Some(path)
+}
+
+/// Searches for a shortest path from the given board, collecting statistics about the search.
+///
+/// Each state is searched along with the key of its board, so the key is computed only once, when the state
+/// is generated by `get_neighbors_with_keys`.
+fn search_from(rule: &Rule, board: &Board) -> (Option<Vec<State>>, SearchStats) {
+    let start_state = (State::new(board), BoardKey::create(rule, board));
+
+    let is_goal = |(s, _): &(State, BoardKey)| rule.is_finished(&s.board);
+    let neighbors = |(s, _): &(State, BoardKey)| get_neighbors_with_keys(rule, s);
+
+    let mut stats = SearchStats::default();
+    let mut visited = new_visited(rule);
+    let try_visit = |(s, key): &(State, BoardKey), depth: usize| {
+        let is_new = visited.try_visit(visit_key_with(rule, s, key), depth);
+        if is_new {
+            stats.record(depth);
+        }
+        is_new
     };
 
+    let path = bfs::find_path(&start_state, is_goal, neighbors, try_visit)
+        .map(|path| path.into_iter().map(|(state, _)| state).collect());
+    (path, stats)
+}
+
+/// Solves the klotski puzzle like `solve`, computing the next states of each depth level in parallel.
+#[cfg(feature = "parallel")]
+pub fn solve_parallel(rule: &Rule) -> Option<Vec<State>> {
+    let start_state = State::new(&rule.start);
+
     let is_goal = |s: &State| rule.is_finished(&s.board);
     let neighbors = |s: &State| get_neighbors(rule, s);
 
-    let mut visited = VisitedHistory::new();
+    let mut visited = new_visited(rule);
+    let try_visit = |s: &State, depth: usize| visited.try_visit(visit_key(rule, s), depth);
+
+    let path = bfs::find_path_parallel(&start_state, is_goal, neighbors, try_visit);
+    if let Some(path) = &path {
+        log_solution(path);
+    }
+    path
+}
+
+/// Solves the klotski puzzle like `solve`, but gives up once the search has taken longer than `timeout`.
+pub fn solve_with_timeout(rule: &Rule, timeout: Duration) -> SolveOutcome {
+    let start_state = State::new(&rule.start);
+
+    let is_goal = |s: &State| rule.is_finished(&s.board);
+    let neighbors = |s: &State| get_neighbors(rule, s);
+
+    let mut visited = new_visited(rule);
+    let try_visit = |s: &State, depth: usize| visited.try_visit(visit_key(rule, s), depth);
+
+    match bfs::find_path_with_timeout(&start_state, is_goal, neighbors, try_visit, timeout) {
+        SearchOutcome::Found(path) => SolveOutcome::Solved(path),
+        SearchOutcome::NotFound => SolveOutcome::Unsolvable,
+        SearchOutcome::BudgetExceeded | SearchOutcome::TimedOut => SolveOutcome::TimedOut,
+    }
+}
+
+/// Solves the klotski puzzle, falling back to a greedy search for very hard instances.
+///
+/// A breadth-first search is tried first. If it visits more than `budget` states,
+/// a greedy best-first search guided by `Rule::goal_distance` is used instead.
+///
+/// Returns the path and whether it is guaranteed to be optimal, or `None` if the puzzle is unsolvable.
+pub fn solve_best_effort(rule: &Rule, budget: usize) -> Option<(Vec<State>, bool)> {
+    let start_state = State::new(&rule.start);
+
+    let is_goal = |s: &State| rule.is_finished(&s.board);
+    let neighbors = |s: &State| get_neighbors(rule, s);
+
+    let mut visited = new_visited(rule);
+    let try_visit = |s: &State, depth: usize| visited.try_visit(visit_key(rule, s), depth);
+
+    match bfs::find_path_with_budget(&start_state, is_goal, neighbors, try_visit, budget) {
+        SearchOutcome::Found(path) => return Some((path, true)),
+        SearchOutcome::NotFound => return None,
+        SearchOutcome::BudgetExceeded | SearchOutcome::TimedOut => {
+            log::info!("Node budget exceeded; falling back to greedy search.");
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let try_visit = |s: &State| visited.insert(visit_key(rule, s));
+    let heuristic = |s: &State| rule.goal_distance(&s.board);
+
+    greedy::find_path(&start_state, is_goal, neighbors, try_visit, heuristic)
+        .map(|path| (path, false))
+}
+
+/// Solves the klotski puzzle within at most `cap` moves, returning a partial solution if that is not enough.
+///
+/// If no goal can be reached within the cap, the path leads to the board, among those within the cap,
+/// whose large piece is closest to the goal by `Rule::goal_distance`, preferring the fewest moves.
+///
+/// Returns the path and whether it reaches the goal.
+pub fn solve_partial(rule: &Rule, cap: usize) -> (Vec<State>, bool) {
+    let start_state = State::new(&rule.start);
+    let neighbors = |s: &State| get_neighbors(rule, s);
+
+    let mut best = (rule.goal_distance(&rule.start), rule.start.clone());
+    let mut visited = new_visited(rule);
+    let try_visit = |s: &State, depth: usize| {
+        if depth > cap || !visited.try_visit(visit_key(rule, s), depth) {
+            return false;
+        }
+        let distance = rule.goal_distance(&s.board);
+        if distance < best.0 {
+            best = (distance, s.board.clone());
+        }
+        true
+    };
+    let is_goal = |s: &State| rule.is_finished(&s.board);
+    if let Some(path) = bfs::find_path(&start_state, is_goal, neighbors, try_visit) {
+        return (path, true);
+    }
+
+    // Search again for the path to the closest board found.
+    let best_board = best.1;
+    let mut visited = new_visited(rule);
     let try_visit =
-        |s: &State, depth: usize| visited.try_visit(BoardKey::create(rule, &s.board), depth);
+        |s: &State, depth: usize| depth <= cap && visited.try_visit(visit_key(rule, s), depth);
+    let is_best = |s: &State| s.board == best_board;
+    let path = bfs::find_path(&start_state, is_best, neighbors, try_visit)
+        .expect("the closest board was found within the cap");
+    (path, false)
+}
+
+/// Solves the klotski puzzle, minimizing the total cost of the moves given by `Rule::weights`.
+///
+/// With the default weights of 1, the path found is as short as the one found by `solve`.
+/// Boards that differ by swapping the pieces of a symmetry pair are only merged if the two pieces weigh the same.
+pub fn solve_weighted(rule: &Rule) -> Option<Vec<State>> {
+    let start_state = State::new(&rule.start);
+
+    let is_goal = |s: &State| rule.is_finished(&s.board);
+    let neighbors = |s: &State| get_neighbors(rule, s);
+    // The pieces that may not move next change the cost of the rest of the path, so they are part of the key.
+    // Swapping the pieces of a pair swaps them there too, and is only harmless if the pair weighs the same.
+    let unpaired = Rule {
+        pairs: vec![],
+        ..rule.clone()
+    };
+    let use_pairs = !rule.pairs.is_empty()
+        && rule
+            .pairs
+            .iter()
+            .all(|&(p, q)| rule.weight_of(p) == rule.weight_of(q));
+    let partner = |piece: Piece| {
+        rule.pairs
+            .iter()
+            .find_map(|&(p, q)| match piece {
+                _ if piece == p => Some(q),
+                _ if piece == q => Some(p),
+                _ => None,
+            })
+            .unwrap_or(piece)
+    };
+    let key = |s: &State| {
+        let tabu_pieces = s.tabu_pieces(rule.tabu_window);
+        let plain = (
+            BoardKey::create(&unpaired, &s.board).get_pattern(),
+            tabu_pieces,
+        );
+        if !use_pairs {
+            return plain;
+        }
+        let swapped = Board::from_bitpattern(s.board.pattern.symmetrized(&rule.pairs));
+        let swapped = (
+            BoardKey::create(&unpaired, &swapped).get_pattern(),
+            tabu_pieces.map(partner),
+        );
+        plain.min(swapped)
+    };
+    let cost = |s: &State| s.piece.map_or(0, |p| rule.weight_of(p));
+
+    dijkstra::find_path(&start_state, is_goal, neighbors, key, cost)
+}
+
+/// Solves the klotski puzzle using the A* search algorithm guided by the given heuristic.
+///
+/// The path found is as short as the one found by `solve`, as long as the heuristic never overestimates.
+/// The statistics count the states whose neighbors were expanded, which a better heuristic reduces.
+pub fn solve_astar(rule: &Rule, heuristic: Heuristic) -> (Option<Vec<State>>, SearchStats) {
+    let start_state = State::new(&rule.start);
+
+    let is_goal = |s: &State| rule.is_finished(&s.board);
+    let neighbors = |s: &State| get_neighbors(rule, s);
+    let key = |s: &State| visit_key(rule, s);
+    let estimate = |s: &State| heuristic.estimate(rule, &s.board);
+
+    let mut stats = SearchStats::default();
+    let path = astar::find_path(&start_state, is_goal, neighbors, key, estimate, |depth| {
+        stats.record(depth)
+    });
+    (path, stats)
+}
+
+/// Finds all shortest solutions of the klotski puzzle.
+///
+/// If `fold_symmetry` is `true`, boards that `BoardKey` treats as equivalent (such as mirror images)
+/// are merged, so solutions that differ only by symmetry are returned once.
+/// If `false`, every sequence of distinct boards is returned, including mirrored ones.
+///
+/// Returns an empty list if the puzzle is unsolvable.
+pub fn solve_all_shortest(rule: &Rule, fold_symmetry: bool) -> Vec<Vec<State>> {
+    solve_some_shortest(rule, fold_symmetry, usize::MAX)
+}
+
+/// Finds up to `max_solutions` shortest solutions of the klotski puzzle, like `solve_all_shortest`.
+///
+/// The enumeration of the solutions stops as soon as enough are found, which bounds the time
+/// when there are very many of them. The breadth-first search finding the shortest length is not bounded.
+pub fn solve_some_shortest(
+    rule: &Rule,
+    fold_symmetry: bool,
+    max_solutions: usize,
+) -> Vec<Vec<State>> {
+    let key = |board: &Board| {
+        if fold_symmetry {
+            BoardKey::create(rule, board).get_pattern()
+        } else {
+            board.pattern
+        }
+    };
+
+    let Some(on_path) = shortest_path_layers(rule, &key) else {
+        return vec![];
+    };
+
+    let mut paths = vec![];
+    collect_shortest_paths(
+        rule,
+        &key,
+        &on_path,
+        max_solutions,
+        &mut vec![State::new(&rule.start)],
+        &mut paths,
+    );
+    paths
+}
+
+/// Finds a shortest solution of the klotski puzzle that keeps the two blanks together as often as possible.
+///
+/// Among the shortest solutions, the one with the most boards whose empty cells form a single region
+/// (see `Board::empty_regions`) is returned. Ties are broken by the order of `get_neighbors`.
+///
+/// Returns `None` if the puzzle is unsolvable.
+pub fn solve_prefer_adjacent_blanks(rule: &Rule) -> Option<Vec<State>> {
+    let adjacent = |board: &Board| usize::from(board.empty_regions() == 1);
+    best_shortest_path(rule, adjacent(&rule.start), |score, state| {
+        score + adjacent(&state.board)
+    })
+}
+
+/// Finds a shortest solution of the klotski puzzle whose longest single move is as short as possible.
+///
+/// A move of one cell has a displacement of 1 and a move of the same piece twice has a displacement of 2,
+/// so among the shortest solutions, one moving every piece a single cell at a time is preferred.
+/// Ties are broken by the order of `get_neighbors`.
+///
+/// Returns `None` if the puzzle is unsolvable.
+pub fn solve_min_max_displacement(rule: &Rule) -> Option<Vec<State>> {
+    best_shortest_path(rule, Reverse(0), |Reverse(longest), state| {
+        Reverse(longest.max(state.path.directions().len()))
+    })
+}
 
-    bfs::find_path(&start_state, is_goal, neighbors, try_visit)
+/// Finds the shortest solution with the highest score, adding each state to the score of the path up to it.
+///
+/// Only the best score up to each state is kept, which finds the best path as long as a better score
+/// up to a state never leads to a worse score later. Ties are broken by the order of `get_neighbors`.
+fn best_shortest_path<S: Copy + Ord>(
+    rule: &Rule,
+    start_score: S,
+    score_step: impl Fn(S, &State) -> S,
+) -> Option<Vec<State>> {
+    let key = |board: &Board| BoardKey::create(rule, board).get_pattern();
+    let on_path = shortest_path_layers(rule, &key)?;
+
+    // For each state on a shortest solution, the best score up to it,
+    // and the index of its previous state in the previous layer.
+    let start_state = State::new(&rule.start);
+    let mut layers = vec![vec![(start_score, start_state, 0)]];
+    for next_keys in &on_path[1..] {
+        let mut indices: HashMap<(BitPattern, RecentPieces), usize> = HashMap::new();
+        let mut next_layer: Vec<(S, State, usize)> = vec![];
+        for (i, (score, state, _)) in layers.last().unwrap().iter().enumerate() {
+            for next_state in get_neighbors(rule, state) {
+                let next_key = key(&next_state.board);
+                if !next_keys.contains(&next_key) {
+                    continue;
+                }
+                let next_score = score_step(*score, &next_state);
+                let tabu_pieces = next_state.tabu_pieces(rule.tabu_window);
+                match indices.entry((next_key, tabu_pieces)) {
+                    Entry::Occupied(entry) => {
+                        let best = &mut next_layer[*entry.get()];
+                        if next_score > best.0 {
+                            *best = (next_score, next_state, i);
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(next_layer.len());
+                        next_layer.push((next_score, next_state, i));
+                    }
+                }
+            }
+        }
+        layers.push(next_layer);
+    }
+
+    // Walk back from the best finished state, taking the first one among equals.
+    let (mut index, _) = layers
+        .last()?
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, state, _))| rule.is_finished(&state.board))
+        .rev()
+        .max_by_key(|(_, (score, _, _))| *score)?;
+    let mut path = vec![];
+    for layer in layers.iter().rev() {
+        let (_, state, previous) = &layer[index];
+        path.push(state.clone());
+        index = *previous;
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Finds, for each depth, the keys of the boards that lie on a shortest solution.
+///
+/// Returns `None` if the puzzle is unsolvable.
+fn shortest_path_layers(
+    rule: &Rule,
+    key: &impl Fn(&Board) -> BitPattern,
+) -> Option<Vec<HashSet<BitPattern>>> {
+    // Breadth-first search recording, for each board of each layer, the boards of the previous layer leading to it.
+    let start_state = State::new(&rule.start);
+    let mut seen = HashSet::from([key(&start_state.board)]);
+    let mut layers = vec![HashMap::from([(key(&start_state.board), vec![])])];
+    let mut frontier = vec![start_state.clone()];
+    let goals = loop {
+        let goals = frontier
+            .iter()
+            .filter(|s| rule.is_finished(&s.board))
+            .map(|s| key(&s.board))
+            .collect::<HashSet<_>>();
+        if !goals.is_empty() {
+            break goals;
+        }
+        if frontier.is_empty() {
+            // Not Found.
+            return None;
+        }
+
+        let mut next_layer: HashMap<BitPattern, Vec<BitPattern>> = HashMap::new();
+        let mut next_frontier = vec![];
+        for state in &frontier {
+            let parent = key(&state.board);
+            for next_state in get_neighbors(rule, state) {
+                let next_key = key(&next_state.board);
+                if let Some(parents) = next_layer.get_mut(&next_key) {
+                    if !parents.contains(&parent) {
+                        parents.push(parent);
+                    }
+                } else if seen.insert(next_key) {
+                    next_layer.insert(next_key, vec![parent]);
+                    next_frontier.push(next_state);
+                }
+            }
+        }
+        layers.push(next_layer);
+        frontier = next_frontier;
+    };
+
+    // Walk back from the goals to find the boards of each layer that lie on a shortest solution.
+    let mut on_path = vec![goals];
+    for layer in layers[1..].iter().rev() {
+        let parents = on_path
+            .last()
+            .unwrap()
+            .iter()
+            .flat_map(|k| layer[k].iter().copied())
+            .collect();
+        on_path.push(parents);
+    }
+    on_path.reverse();
+    Some(on_path)
+}
+
+/// Extends the path along the boards on a shortest solution, collecting each completed path
+/// until there are `max_paths` of them.
+fn collect_shortest_paths(
+    rule: &Rule,
+    key: &impl Fn(&Board) -> BitPattern,
+    on_path: &[HashSet<BitPattern>],
+    max_paths: usize,
+    path: &mut Vec<State>,
+    paths: &mut Vec<Vec<State>>,
+) {
+    if paths.len() >= max_paths {
+        return;
+    }
+    let Some(next_keys) = on_path.get(path.len()) else {
+        paths.push(path.clone());
+        return;
+    };
+    let mut visited = HashSet::new();
+    for next_state in get_neighbors(rule, path.last().unwrap()) {
+        let next_key = key(&next_state.board);
+        if next_keys.contains(&next_key) && visited.insert(next_key) {
+            path.push(next_state);
+            collect_shortest_paths(rule, key, on_path, max_paths, path, paths);
+            path.pop();
+        }
+    }
+}
+
+/// Checks that the path is a solution of the rule: it starts from the rule's starting board,
+/// each step is a legal move of the recorded piece and path, which is not locked and passes through no forbidden board,
+/// and the last board is finished.
+pub fn verify_solution(rule: &Rule, path: &[State]) -> bool {
+    let (Some(first), Some(last)) = (path.first(), path.last()) else {
+        return false;
+    };
+    first.board == rule.start
+        && rule.is_finished(&last.board)
+        && path
+            .windows(2)
+            .all(|pair| apply_step(rule, &pair[0].board, &pair[1]).as_ref() == Some(&pair[1].board))
+}
+
+/// Applies the move recorded in the state to the board, returning `None` if it is illegal.
+fn apply_step(rule: &Rule, board: &Board, state: &State) -> Option<Board> {
+    let piece = state.piece?;
+    if rule.locked.contains(&piece) {
+        return None;
+    }
+    let directions = state.path.directions();
+    if directions.is_empty() {
+        return None;
+    }
+    directions
+        .into_iter()
+        .try_fold(board.clone(), |board, direction| {
+            rule.move_piece(&board, piece, direction)
+                .filter(|next_board| !rule.is_forbidden(next_board))
+        })
 }
 
 /// Creates the next possible states from the current state based on the given rule.
 pub fn get_neighbors(rule: &Rule, state: &State) -> Vec<State> {
     let mut next_states = vec![];
     let current_board = &state.board;
+    let tabu_pieces = state.tabu_pieces(rule.tabu_window);
+    let earlier_pieces = next_earlier_pieces(rule, state);
+    // Without wrapping, skip the pieces found blocked for all the pieces at once.
+    // The sets are indexed by the discriminant of each direction.
+    let movable_ids = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ]
+    .map(|direction| match rule.wrap {
+        true => u16::MAX,
+        false => current_board.movable_ids(direction, &state.empties),
+    });
     for &piece in &rule.pieces {
-        if let Some(prev_piece) = state.piece
-            && prev_piece == piece
-        {
-            // Do not move the same piece as last time.
+        if tabu_pieces.contains(piece) || rule.locked.contains(&piece) {
+            // Do not move the same piece again within the tabu window, nor a locked piece.
             continue;
         }
-        for &direction in ALL_DIRECTIONS {
-            if let Some(next_board) = current_board.move_piece(piece, direction) {
+        let piece_mask = current_board.pattern.mask_of(piece);
+        if piece_mask.is_empty() {
+            continue;
+        }
+        for &direction in &rule.direction_order {
+            if movable_ids[direction as usize] & (1 << piece.id) == 0 {
+                continue;
+            }
+            if let Some((next_board, next_mask)) =
+                rule.move_piece_fast(current_board, &piece_mask, direction)
+            {
+                if rule.is_forbidden(&next_board) {
+                    // Neither stop on nor pass through a forbidden board.
+                    continue;
+                }
                 // Move a piece in a certain direction.
+                // The cells it leaves become empty and the cells it enters are no longer empty.
+                let next_empties = (state.empties | piece_mask) & !next_mask;
                 let next_state = State {
                     board: next_board.clone(),
                     piece: Some(piece),
                     path: MovePath::One(direction),
+                    earlier_pieces,
+                    empties: next_empties,
                 };
                 next_states.push(next_state);
 
                 // There are two blank spaces on the board.
                 // In some cases, the player can move the same piece twice.
-                for &direction2 in ALL_DIRECTIONS {
-                    if direction.reversed() == direction2 {
+                for &direction2 in &rule.direction_order {
+                    if direction.reversed() == direction2 && !rule.allow_reversal {
                         // Do not move in the opposite direction immediately.
                         continue;
                     }
-                    if let Some(next2_board) = next_board.move_piece(piece, direction2) {
+                    if let Some((next2_board, next2_mask)) =
+                        rule.move_piece_fast(&next_board, &next_mask, direction2)
+                        && !rule.is_forbidden(&next2_board)
+                    {
                         // Move the same piece once more.
                         let next2_state = State {
                             board: next2_board,
                             piece: Some(piece),
                             path: MovePath::Two(direction, direction2),
+                            earlier_pieces,
+                            empties: (next_empties | next_mask) & !next2_mask,
                         };
                         next_states.push(next2_state);
                     }
@@ -74,46 +675,797 @@ pub fn get_neighbors(rule: &Rule, state: &State) -> Vec<State> {
             }
         }
     }
-    next_states
-}
+    next_states
+}
+
+/// Creates the next possible states like `get_neighbors`, each paired with the key of its board.
+///
+/// The key is computed once here, so callers that track visited boards need not compute it again.
+pub fn get_neighbors_with_keys(rule: &Rule, state: &State) -> Vec<(State, BoardKey)> {
+    get_neighbors(rule, state)
+        .into_iter()
+        .map(|next_state| {
+            let key = BoardKey::create(rule, &next_state.board);
+            (next_state, key)
+        })
+        .collect()
+}
+
+/// Returns the pieces moved before the next move from the state, as many as the rule's tabu window needs.
+///
+/// The piece of the next move is not included, since it is kept in `State::piece`.
+pub(super) fn next_earlier_pieces(rule: &Rule, state: &State) -> RecentPieces {
+    match state.piece {
+        Some(piece) => state
+            .earlier_pieces
+            .pushed(piece, rule.tabu_window.saturating_sub(1)),
+        None => RecentPieces::default(),
+    }
+}
+
+/// Returns the key of the state in the visited sets of the searches.
+///
+/// With a tabu window of more than one move, the pieces that may not move next differ between states of
+/// the same board, so they are part of the key. Otherwise the board alone decides the moves that follow.
+fn visit_key(rule: &Rule, state: &State) -> (u128, u64) {
+    visit_key_with(rule, state, &BoardKey::create(rule, &state.board))
+}
+
+/// Returns the key of the state like `visit_key`, from the key of its board computed beforehand.
+fn visit_key_with(rule: &Rule, state: &State, board_key: &BoardKey) -> (u128, u64) {
+    let board_key = board_key.get_raw();
+    match rule.tabu_window {
+        0 | 1 => (board_key, 0),
+        window => (board_key, state.tabu_pieces(window).packed()),
+    }
+}
+
+/// Creates the visited set of a breadth-first search under the rule.
+///
+/// With a tabu window of more than one move, a state may be reached again long after it was visited,
+/// since its moves cannot be undone at once, so the set keeps every generation.
+fn new_visited(rule: &Rule) -> VisitedHistory<(u128, u64)> {
+    match rule.tabu_window {
+        0 | 1 => VisitedHistory::new(),
+        _ => VisitedHistory::keeping_all(),
+    }
+}
+
+/// Represents a unique key for a board state, which is used to identify and compare different board configurations.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BoardKey {
+    key: BitPattern,
+}
+
+/// The shape classes of a cell, packed by `BoardKey::as_u64`.
+const CELL_EMPTY: u64 = 0;
+const CELL_SMALL: u64 = 1;
+const CELL_HORIZONTAL_LEFT: u64 = 2;
+const CELL_HORIZONTAL_RIGHT: u64 = 3;
+const CELL_VERTICAL_TOP: u64 = 4;
+const CELL_VERTICAL_BOTTOM: u64 = 5;
+const CELL_LARGE: u64 = 6;
+
+impl BoardKey {
+    /// Creates a new `BoardKey` based on the provided rule and board.
+    pub fn create(rule: &Rule, board: &Board) -> BoardKey {
+        let mut key = Self::horizontal_key(rule, board.pattern);
+
+        if rule.vertical_symmetry {
+            // In the case of a goal symmetrical top-to-bottom.
+            let flipped = board.pattern.flipped_vertically();
+            key = key.min(Self::horizontal_key(rule, flipped));
+        }
+
+        BoardKey { key }
+    }
+
+    /// Creates the key of a board reached from the parent by moving a single piece, like `create`.
+    ///
+    /// See `KeyContext::child` for how the key is computed from the parent's images.
+    pub fn create_from_parent(parent: &KeyContext, board: &Board) -> BoardKey {
+        parent.child(board).key()
+    }
+
+    /// Returns the raw image of the canonical bit pattern, for compact storage of visited boards.
+    pub fn get_raw(&self) -> u128 {
+        self.key.get_u128()
+    }
+
+    /// Returns the canonical bit pattern of the key.
+    pub fn get_pattern(&self) -> BitPattern {
+        self.key
+    }
+
+    /// Packs the key into 64 bits by storing the shape class of each cell instead of its piece ID.
+    ///
+    /// Each cell takes 3 bits, from the top-left cell in the most significant bits.
+    /// Pieces of the same shape become indistinguishable, which is only harmless when the rule tells
+    /// the other pieces apart by nothing but their shape: no piece goals, weights, locked pieces,
+    /// forbidden boards or tabu window of more than one move. The caller must check that for its rule.
+    /// Returns `None` if there is another large piece, or a piece of an irregular shape.
+    pub fn as_u64(&self) -> Option<u64> {
+        pack_shape_classes(self.key)
+    }
+
+    /// Returns the smallest pattern among the left-to-right symmetric variants of the given pattern.
+    fn horizontal_key(rule: &Rule, pattern: BitPattern) -> BitPattern {
+        if !rule.horizontal_symmetry {
+            // The goal is not symmetric left-to-right, so a board and its mirror image differ.
+            return pattern;
+        }
+        let mut key = pattern.min(pattern.mirrored());
+
+        if !rule.pairs.is_empty() && rule.tabu_window <= 1 {
+            // In the case of a symmetrical board at the start.
+            // Swapping the pairs would also swap the pieces kept by a longer tabu window, so it is skipped then.
+            let symmetrized = pattern.symmetrized(&rule.pairs);
+            key = key.min(symmetrized).min(symmetrized.mirrored());
+        }
+
+        key
+    }
+}
+
+/// The symmetric images of a board that `BoardKey::create` takes the smallest of, kept so that the key
+/// of a neighboring board can be computed incrementally.
+///
+/// `get_neighbors` and the searches do not use it. Along a path of the default puzzle, `child` and `key`
+/// take about 17% longer than `BoardKey::create` in a release build, since mirroring the changed cells
+/// costs as much as mirroring the board, and each state would also have to carry the eight images.
+#[derive(Clone, Debug)]
+pub struct KeyContext {
+    /// The board, its mirror image, and the same two with the symmetry pairs swapped.
+    /// With vertical symmetry, the four images flipped vertically follow.
+    images: [BitPattern; 8],
+    /// The ID swapped with each piece ID by the symmetry pairs, or the ID itself.
+    partners: [u8; 16],
+    horizontal_symmetry: bool,
+    use_pairs: bool,
+    vertical_symmetry: bool,
+}
+
+impl KeyContext {
+    /// Creates the images of the board from scratch.
+    pub fn new(rule: &Rule, board: &Board) -> Self {
+        let mut partners = std::array::from_fn(|id| id as u8);
+        for &(a, b) in &rule.pairs {
+            partners[a.id as usize] = b.id;
+            partners[b.id as usize] = a.id;
+        }
+        let pattern = board.pattern;
+        let symmetrized = pattern.symmetrized(&rule.pairs);
+        let flipped = pattern.flipped_vertically();
+        let flipped_symmetrized = symmetrized.flipped_vertically();
+        Self {
+            images: [
+                pattern,
+                pattern.mirrored(),
+                symmetrized,
+                symmetrized.mirrored(),
+                flipped,
+                flipped.mirrored(),
+                flipped_symmetrized,
+                flipped_symmetrized.mirrored(),
+            ],
+            partners,
+            horizontal_symmetry: rule.horizontal_symmetry,
+            use_pairs: rule.horizontal_symmetry && !rule.pairs.is_empty() && rule.tabu_window <= 1,
+            vertical_symmetry: rule.vertical_symmetry,
+        }
+    }
+
+    /// Returns the images of a board reached from this one by moving a single piece.
+    ///
+    /// Mirroring and flipping only rearrange cells, so each image changes by the image of the cells
+    /// that changed. Swapping the symmetry pairs changes those cells to the partner of the moved piece.
+    /// This saves computing the masks of the paired pieces, though the mirroring is still done once
+    /// for the changed cells. The board must differ from this one by the move of one piece only.
+    pub fn child(&self, board: &Board) -> Self {
+        let delta = self.images[0] ^ board.pattern;
+        let value = delta.get_u128();
+        if value == 0 {
+            return self.clone();
+        }
+        let piece = ((value >> (value.trailing_zeros() / 4 * 4)) & 0xf) as usize;
+        let partner = self.partners[piece] as u128 * 0x1111_1111_1111_1111_1111;
+        let swapped_delta = !delta.mask_of(Piece::new(0)) & BitPattern::new(partner);
+
+        let deltas = [
+            delta,
+            delta.mirrored(),
+            swapped_delta,
+            swapped_delta.mirrored(),
+        ];
+        let mut images = self.images;
+        for (image, delta) in images[..4].iter_mut().zip(deltas) {
+            *image = *image ^ delta;
+        }
+        if self.vertical_symmetry {
+            for (image, delta) in images[4..].iter_mut().zip(deltas) {
+                *image = *image ^ delta.flipped_vertically();
+            }
+        }
+        Self { images, ..*self }
+    }
+
+    /// Returns the key of the board, the smallest of the images that `BoardKey::create` compares.
+    pub fn key(&self) -> BoardKey {
+        // The bits of the index of an image tell whether it is mirrored, swapped and flipped.
+        let is_used = |i: usize| {
+            (i & 1 == 0 || self.horizontal_symmetry)
+                && (i & 2 == 0 || self.use_pairs)
+                && (i & 4 == 0 || self.vertical_symmetry)
+        };
+        let key = (0..8)
+            .filter(|&i| is_used(i))
+            .map(|i| self.images[i])
+            .min()
+            .unwrap();
+        BoardKey { key }
+    }
+}
+
+/// Packs the pattern into 64 bits by storing the shape class of each cell, as described in `BoardKey::as_u64`.
+pub(super) fn pack_shape_classes(pattern: BitPattern) -> Option<u64> {
+    let grid = Board::from_bitpattern(pattern).to_grid();
+    let mut cell_counts = [0; 16];
+    for &id in grid.iter().flatten() {
+        cell_counts[id as usize] += 1;
+    }
+    let same_id = |row: usize, col: usize, id: u8| {
+        grid.get(row).and_then(|cells| cells.get(col)) == Some(&id)
+    };
+
+    let mut packed = 0u64;
+    for (row, cells) in grid.iter().enumerate() {
+        for (col, &id) in cells.iter().enumerate() {
+            let class = match (id, cell_counts[id as usize]) {
+                (0, _) => CELL_EMPTY,
+                (_, 1) => CELL_SMALL,
+                (_, 2) if same_id(row, col + 1, id) => CELL_HORIZONTAL_LEFT,
+                (_, 2) if col > 0 && same_id(row, col - 1, id) => CELL_HORIZONTAL_RIGHT,
+                (_, 2) if same_id(row + 1, col, id) => CELL_VERTICAL_TOP,
+                (_, 2) if row > 0 && same_id(row - 1, col, id) => CELL_VERTICAL_BOTTOM,
+                (1, 4) => CELL_LARGE,
+                _ => return None,
+            };
+            packed = (packed << 3) | class;
+        }
+    }
+    Some(packed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_solve_returns_none_for_unsolvable() {
+        // Arrange: Test solve returns None for unsolvable puzzle
+        let rule = Rule::new(
+            &Board::new(0x2112_2112_3344_5678_5008),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        // Act
+        let result = solve(&rule);
+        // Assert
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_solve_with_stats_for_unsolvable() {
+        // Arrange: Test solve_with_stats keeps the stats when no solution is found
+        let rule = Rule::new(
+            &Board::new(0x2112_2112_3344_5678_5008),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        // Act
+        let (result, stats) = solve_with_stats(&rule);
+        // Assert
+        assert_eq!(result, None);
+        assert!(stats.max_depth > 0);
+        assert!(stats.visited_states > 0);
+    }
+
+    /// A logger that keeps the messages logged by the tests.
+    struct TestLogger {
+        messages: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl log::Log for TestLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Info
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                let mut messages = self.messages.lock().unwrap();
+                messages.push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static TEST_LOGGER: TestLogger = TestLogger {
+        messages: std::sync::Mutex::new(vec![]),
+    };
+
+    #[test]
+    fn test_solve_logs_solution_summary() {
+        // Arrange
+        let _ = log::set_logger(&TEST_LOGGER);
+        log::set_max_level(log::LevelFilter::Info);
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+
+        // Act
+        let path = solve(&rule).unwrap();
+
+        // Assert
+        let expected = format!("Solved in {} moves: ", path.len() - 1);
+        let messages = TEST_LOGGER.messages.lock().unwrap();
+        assert!(messages.iter().any(|m| m.starts_with(&expected)));
+    }
+
+    #[test]
+    fn test_solve_avoidance_goal() {
+        // Arrange: The large piece must leave the top center
+        let rule = Rule::with_goal(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            GoalCondition::Avoids(BitPattern::new(0x0ff0_0ff0_0000_0000_0000)),
+        );
+        // Act
+        let path = solve(&rule).unwrap();
+        // Assert
+        assert!(path.len() > 1);
+        assert!(rule.is_finished(&path.last().unwrap().board));
+
+        // Arrange: Already satisfied at the start
+        let rule = Rule::with_goal(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            GoalCondition::Avoids(BitPattern::new(0x0000_0000_0000_0ff0_0ff0)),
+        );
+        // Act
+        let path = solve(&rule).unwrap();
+        // Assert
+        assert_eq!(path, vec![State::new(&rule.start)]);
+    }
+
+    #[test]
+    fn test_board_key_create_from_parent_matches_create() {
+        // Arrange: One rule with symmetry pairs, one with vertical symmetry as well
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let rules = [
+            Rule::new(&start, &BitPattern::new(0x0000_0000_0000_0ff0_0ff0)),
+            Rule::with_goal(
+                &start,
+                GoalCondition::Avoids(BitPattern::new(0x0000_0000_ffff_0000_0000)),
+            ),
+        ];
+        assert!(!rules[0].pairs.is_empty());
+        assert!(rules[1].vertical_symmetry);
+        for rule in &rules {
+            let path = random_walk(&rule.start, 200, 3);
+            let mut context = KeyContext::new(rule, &rule.start);
+            assert_eq!(context.key(), BoardKey::create(rule, &rule.start));
+            for state in &path[1..] {
+                // Act
+                let key = BoardKey::create_from_parent(&context, &state.board);
+                context = context.child(&state.board);
+                // Assert
+                assert_eq!(key, BoardKey::create(rule, &state.board));
+                assert_eq!(context.key(), key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_min_max_displacement() {
+        // Arrange: The path found by solve moves a piece two cells at once, but need not
+        let rule = Rule::new(
+            &Board::new(0x2040_1143_1100_5566_7899),
+            &BitPattern::new(0x0000_0ff0_0ff0_0000_0000),
+        );
+        let longest_move = |path: &[State]| {
+            path.iter()
+                .map(|state| state.path.directions().len())
+                .max()
+                .unwrap()
+        };
+        let shortest = solve(&rule).unwrap();
+
+        // Act
+        let path = solve_min_max_displacement(&rule).unwrap();
+
+        // Assert
+        assert_eq!(path.len(), shortest.len());
+        assert!(verify_solution(&rule, &path));
+        assert_eq!(longest_move(&shortest), 2);
+        assert_eq!(longest_move(&path), 1);
+    }
+
+    #[test]
+    fn test_verify_solution() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let path = solve(&rule).unwrap();
+
+        // Act & Assert
+        assert!(verify_solution(&rule, &path));
+        assert!(!verify_solution(&rule, &path[..path.len() - 1]));
+        assert!(!verify_solution(&rule, &path[1..]));
+        assert!(!verify_solution(&rule, &[]));
+
+        // Arrange: Record a different move for the first step
+        let mut tampered = path.clone();
+        tampered[1].path = match tampered[1].path {
+            MovePath::One(direction) => MovePath::One(direction.reversed()),
+            _ => MovePath::None,
+        };
+        // Act & Assert
+        assert!(!verify_solution(&rule, &tampered));
+    }
+
+    #[test]
+    fn test_solve_partial() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let shortest_len = solve(&rule).unwrap().len();
+
+        // Act: Enough moves
+        let (path, reached_goal) = solve_partial(&rule, shortest_len - 1);
+        // Assert
+        assert!(reached_goal);
+        assert_eq!(path.len(), shortest_len);
+
+        // Act: Too few moves
+        let (path, reached_goal) = solve_partial(&rule, shortest_len - 2);
+        // Assert
+        assert!(!reached_goal);
+        assert!(path.len() < shortest_len);
+        assert!(verify_path_moves(&rule, &path));
+        let last_board = &path.last().unwrap().board;
+        assert!(rule.goal_distance(last_board) < rule.goal_distance(&rule.start));
+    }
+
+    /// Checks that each step of the path is a legal move from the previous board.
+    fn verify_path_moves(rule: &Rule, path: &[State]) -> bool {
+        path.windows(2)
+            .all(|pair| apply_step(rule, &pair[0].board, &pair[1]).as_ref() == Some(&pair[1].board))
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_solve_parallel() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        // Act
+        let path = solve_parallel(&rule).unwrap();
+        // Assert
+        assert_eq!(path.len(), solve(&rule).unwrap().len());
+        assert!(verify_solution(&rule, &path));
+    }
+
+    #[test]
+    fn test_solve_with_piece_goals() {
+        // Arrange: The horizontal piece #4 must also be at the top center
+        let rule = Rule::with_piece_goals(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+            vec![(Piece::new(4), BitPattern::new(0x0ff0_0000_0000_0000_0000))],
+        );
+        // Act
+        let path = solve(&rule).unwrap();
+        // Assert
+        let last_board = &path.last().unwrap().board;
+        assert!(verify_solution(&rule, &path));
+        assert_eq!(
+            last_board.pattern.mask_of(Piece::new(4)),
+            BitPattern::new(0x0ff0_0000_0000_0000_0000)
+        );
+    }
+
+    #[test]
+    fn test_solve_with_asymmetric_piece_goal() {
+        // Arrange: The small piece #2 must reach the top-right corner, so mirrored boards differ
+        let rule = Rule::with_piece_goals(
+            &Board::new(0x1120_1100_0000_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+            vec![(Piece::new(2), BitPattern::new(0x000f_0000_0000_0000_0000))],
+        );
+        assert!(!rule.horizontal_symmetry);
+
+        // Act
+        let path = solve(&rule).unwrap();
+
+        // Assert: Folding the mirror images took 12 moves
+        assert!(verify_solution(&rule, &path));
+        assert_eq!(path.len() - 1, 3);
+    }
+
+    #[test]
+    fn test_solve_with_timeout() {
+        // Arrange: Test solve_with_timeout gives up on the hard default puzzle
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        // Act
+        let result = solve_with_timeout(&rule, Duration::ZERO);
+        // Assert
+        assert_eq!(result, SolveOutcome::TimedOut);
+
+        // Arrange: Test solve_with_timeout solves and detects unsolvable puzzles in time
+        let easy_rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let unsolvable_rule = Rule::new(
+            &Board::new(0x2112_2112_3344_5678_5008),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        // Act & Assert
+        assert!(matches!(
+            solve_with_timeout(&easy_rule, Duration::from_secs(600)),
+            SolveOutcome::Solved(_)
+        ));
+        assert_eq!(
+            solve_with_timeout(&unsolvable_rule, Duration::from_secs(600)),
+            SolveOutcome::Unsolvable
+        );
+    }
+
+    #[test]
+    fn test_solve_weighted() {
+        // Arrange: Test solve_weighted matches solve with the default weights
+        let mut rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let bfs_path = solve(&rule).unwrap();
+        // Act
+        let weighted_path = solve_weighted(&rule).unwrap();
+        // Assert
+        assert_eq!(weighted_path.len(), bfs_path.len());
+
+        // Arrange: Test solve_weighted avoids an expensive piece
+        rule.weights.insert(Piece::new(4), 100);
+        let total_cost = |path: &[State]| {
+            path.iter()
+                .filter_map(|s| s.piece)
+                .map(|p| rule.weight_of(p))
+                .sum::<usize>()
+        };
+        // Act
+        let weighted_path = solve_weighted(&rule).unwrap();
+        // Assert
+        assert!(rule.is_finished(&weighted_path.last().unwrap().board));
+        assert!(total_cost(&weighted_path) < total_cost(&bfs_path));
+        assert_ne!(weighted_path, bfs_path);
+    }
+
+    #[test]
+    fn test_solve_weighted_with_paired_pieces_of_different_weights() {
+        // Arrange: Pieces #4 and #5 are a symmetry pair, but #4 costs more to move
+        let mut rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        assert!(rule.pairs.contains(&(Piece::new(4), Piece::new(5))));
+        rule.weights.insert(Piece::new(4), 5);
+        let total_cost = |path: &[State]| {
+            path.iter()
+                .filter_map(|s| s.piece)
+                .map(|p| rule.weight_of(p))
+                .sum::<usize>()
+        };
+
+        // Act
+        let path = solve_weighted(&rule).unwrap();
+
+        // Assert: The lowest cost, as found by a search over the raw boards
+        assert!(verify_solution(&rule, &path));
+        assert_eq!(total_cost(&path), 19);
+    }
+
+    #[test]
+    fn test_solve_all_shortest() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let shortest_len = solve(&rule).unwrap().len();
+
+        // Act
+        let folded = solve_all_shortest(&rule, true);
+        let unfolded = solve_all_shortest(&rule, false);
+
+        // Assert
+        for path in folded.iter().chain(&unfolded) {
+            assert_eq!(path.len(), shortest_len);
+            assert!(rule.is_finished(&path.last().unwrap().board));
+        }
+        assert!(!folded.is_empty());
+        assert!(unfolded.len() > folded.len());
+    }
+
+    #[test]
+    fn test_solve_with_wrap() {
+        // Arrange: The large piece can reach the bottom center by wrapping over the top edge
+        let mut rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        rule.wrap = true;
+
+        // Act
+        let path = solve(&rule).unwrap();
+
+        // Assert
+        assert!(verify_solution(&rule, &path));
+        assert!(path.len() - 1 < 81);
+        rule.wrap = false;
+        assert!(!verify_solution(&rule, &path));
+    }
+
+    #[test]
+    fn test_solve_astar() {
+        // Arrange: The default board, with a nearer goal to keep the search short
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0xff00_ff00_0000_0000_0000),
+        );
+        let shortest_len = solve(&rule).unwrap().len();
+
+        // Act
+        let (distance_path, distance_stats) = solve_astar(&rule, Heuristic::Distance);
+        let (blocking_path, blocking_stats) = solve_astar(&rule, Heuristic::Blocking);
+
+        // Assert
+        for path in [distance_path.unwrap(), blocking_path.unwrap()] {
+            assert_eq!(path.len(), shortest_len);
+            assert!(verify_solution(&rule, &path));
+        }
+        assert!(blocking_stats.visited_states < distance_stats.visited_states);
+    }
 
-/// Represents a unique key for a board state, which is used to identify and compare different board configurations.
-#[derive(PartialEq, Eq, Hash, Debug)]
-pub struct BoardKey {
-    key: BitPattern,
-}
+    #[test]
+    fn test_solve_astar_default_goal() {
+        // Arrange: The weaker heuristic, which searches longest, on the default puzzle
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let shortest_len = solve(&rule).unwrap().len();
 
-impl BoardKey {
-    /// Creates a new `BoardKey` based on the provided rule and board.
-    pub fn create(rule: &Rule, board: &Board) -> BoardKey {
-        let mut key = board.pattern.min(board.pattern.mirrored());
+        // Act
+        let (path, _stats) = solve_astar(&rule, Heuristic::Distance);
 
-        if !rule.pairs.is_empty() {
-            // In the case of a symmetrical board at the start.
-            let symmetrized = board.pattern.symmetrized(&rule.pairs);
-            key = key.min(symmetrized).min(symmetrized.mirrored());
+        // Assert
+        let path = path.unwrap();
+        assert_eq!(path.len(), shortest_len);
+        assert!(verify_solution(&rule, &path));
+    }
+
+    #[test]
+    fn test_solve_astar_with_wrap() {
+        // Arrange: The large piece reaches the goal faster across the top edge than the distance says
+        let mut rule = Rule::new(
+            &Board::new(0x0011_0011_2003_2443_0055),
+            &BitPattern::new(0x0000_0000_0000_ff00_ff00),
+        );
+        rule.wrap = true;
+        let shortest_len = solve(&rule).unwrap().len();
+
+        // Act & Assert
+        assert_eq!(shortest_len - 1, 5);
+        for heuristic in [Heuristic::Distance, Heuristic::Blocking] {
+            let path = solve_astar(&rule, heuristic).0.unwrap();
+            assert_eq!(path.len(), shortest_len);
+            assert!(verify_solution(&rule, &path));
         }
+    }
 
-        BoardKey { key }
+    #[test]
+    fn test_solve_some_shortest() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let all = solve_all_shortest(&rule, false);
+
+        // Act
+        let some = solve_some_shortest(&rule, false, 2);
+
+        // Assert
+        assert!(all.len() > 2);
+        assert_eq!(some, all[..2]);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::super::*;
-    use super::*;
+    #[test]
+    fn test_solve_prefer_adjacent_blanks() {
+        // Arrange: Of the shortest solutions to the center, `solve` finds one splitting the blanks
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0ff0_0ff0_0000_0000),
+        );
+        let adjacent_count = |path: &[State]| {
+            path.iter()
+                .filter(|state| state.board.empty_regions() == 1)
+                .count()
+        };
+        let plain = solve(&rule).unwrap();
+        let all = solve_all_shortest(&rule, true);
+
+        // Act
+        let path = solve_prefer_adjacent_blanks(&rule).unwrap();
+
+        // Assert
+        assert!(verify_solution(&rule, &path));
+        assert_eq!(path.len(), plain.len());
+        assert!(adjacent_count(&path) > adjacent_count(&plain));
+        assert_eq!(
+            adjacent_count(&path),
+            all.iter().map(|path| adjacent_count(path)).max().unwrap()
+        );
+    }
 
     #[test]
-    fn test_solve_returns_none_for_unsolvable() {
-        // Arrange: Test solve returns None for unsolvable puzzle
+    fn test_solve_all_shortest_for_unsolvable() {
+        // Arrange
         let rule = Rule::new(
             &Board::new(0x2112_2112_3344_5678_5008),
             &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
         );
+        // Act & Assert
+        assert!(solve_all_shortest(&rule, true).is_empty());
+    }
+
+    #[test]
+    fn test_solve_best_effort_within_budget() {
+        // Arrange: Test solve_best_effort solves the default puzzle optimally
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
         // Act
-        let result = solve(&rule);
+        let result = solve_best_effort(&rule, usize::MAX);
         // Assert
-        assert_eq!(result, None);
+        let (path, optimal) = result.unwrap();
+        assert!(optimal);
+        assert_eq!(path.len(), 82);
+        assert!(rule.is_finished(&path.last().unwrap().board));
+    }
+
+    #[test]
+    fn test_solve_best_effort_over_budget() {
+        // Arrange: Test solve_best_effort falls back to greedy search
+        let rule = Rule::new(
+            &Board::new(0x2211_3311_4455_6078_90a8),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        // Act
+        let result = solve_best_effort(&rule, 100);
+        // Assert
+        let (path, optimal) = result.unwrap();
+        assert!(!optimal);
+        assert!(rule.is_finished(&path.last().unwrap().board));
     }
 
     #[test]
@@ -127,6 +1479,8 @@ mod tests {
             board: rule.start.clone(),
             piece: Some(rule.pieces[0]),
             path: MovePath::None,
+            earlier_pieces: RecentPieces::default(),
+            empties: rule.start.empty_mask(),
         };
 
         // Act
@@ -140,46 +1494,388 @@ mod tests {
                     board: Board::new(0x2113_2113_4556_4086_970a),
                     piece: Some(Piece::new(7)),
                     path: MovePath::One(Direction::Down),
+                    earlier_pieces: RecentPieces::default(),
+                    empties: BitPattern::new(0x0000_0000_0000_0f00_00f0),
                 },
                 State {
                     board: Board::new(0x2113_2113_4556_4086_907a),
                     piece: Some(Piece::new(7)),
                     path: MovePath::Two(Direction::Down, Direction::Right),
+                    earlier_pieces: RecentPieces::default(),
+                    empties: BitPattern::new(0x0000_0000_0000_0f00_0f00),
                 },
                 State {
                     board: Board::new(0x2113_2113_4556_4706_908a),
                     piece: Some(Piece::new(8)),
                     path: MovePath::One(Direction::Down),
+                    earlier_pieces: RecentPieces::default(),
+                    empties: BitPattern::new(0x0000_0000_0000_00f0_0f00),
                 },
                 State {
                     board: Board::new(0x2113_2113_4556_4706_980a),
                     piece: Some(Piece::new(8)),
                     path: MovePath::Two(Direction::Down, Direction::Left),
+                    earlier_pieces: RecentPieces::default(),
+                    empties: BitPattern::new(0x0000_0000_0000_00f0_00f0),
                 },
                 State {
                     board: Board::new(0x2113_2113_4556_4786_090a),
                     piece: Some(Piece::new(9)),
                     path: MovePath::One(Direction::Right),
+                    earlier_pieces: RecentPieces::default(),
+                    empties: BitPattern::new(0x0000_0000_0000_0000_f0f0),
                 },
                 State {
                     board: Board::new(0x2113_2113_4556_4786_009a),
                     piece: Some(Piece::new(9)),
                     path: MovePath::Two(Direction::Right, Direction::Right),
+                    earlier_pieces: RecentPieces::default(),
+                    empties: BitPattern::new(0x0000_0000_0000_0000_ff00),
                 },
                 State {
                     board: Board::new(0x2113_2113_4556_4786_90a0),
                     piece: Some(Piece::new(0xa)),
                     path: MovePath::One(Direction::Left),
+                    earlier_pieces: RecentPieces::default(),
+                    empties: BitPattern::new(0x0000_0000_0000_0000_0f0f),
                 },
                 State {
                     board: Board::new(0x2113_2113_4556_4786_9a00),
                     piece: Some(Piece::new(0xa)),
                     path: MovePath::Two(Direction::Left, Direction::Left),
+                    earlier_pieces: RecentPieces::default(),
+                    empties: BitPattern::new(0x0000_0000_0000_0000_00ff),
                 },
             ]
         );
     }
 
+    #[test]
+    fn test_solve_from_prefix() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0xff00_ff00_0000_0000_0000),
+        );
+        let prefix = [
+            (Piece::new(7), MovePath::One(Direction::Down)),
+            (Piece::new(0xa), MovePath::One(Direction::Left)),
+        ];
+
+        // Act
+        let path = solve_from_prefix(&rule, &prefix).unwrap();
+
+        // Assert
+        assert!(verify_solution(&rule, &path));
+        let moves = path[1..=2]
+            .iter()
+            .map(|state| (state.piece.unwrap(), state.path.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(moves, prefix);
+        assert_eq!(
+            path.len(),
+            2 + solve_from(&rule, &path[2].board).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn test_solve_from_prefix_rejects_illegal_move() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0xff00_ff00_0000_0000_0000),
+        );
+
+        // Act & Assert
+        assert_eq!(
+            solve_from_prefix(&rule, &[(Piece::new(1), MovePath::One(Direction::Down))]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_solve_n_times() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let (expected_path, expected_stats) = solve_with_stats(&rule);
+
+        // Act
+        let (path, stats) = solve_n_times(&rule, 3);
+
+        // Assert
+        assert_eq!(path, expected_path);
+        assert_eq!(stats, expected_stats);
+        assert_eq!(solve_n_times(&rule, 0), (None, SearchStats::default()));
+    }
+
+    #[test]
+    fn test_solve_with_locked_piece() {
+        // Arrange: Test that a locked piece never moves in the solution
+        let locked = Piece::new(2);
+        let rule = Rule::with_locked(
+            &Board::new(0x2113_2113_4556_0000_7008),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+            vec![locked],
+        )
+        .unwrap();
+
+        // Act
+        let path = solve(&rule).unwrap();
+
+        // Assert
+        assert!(verify_solution(&rule, &path));
+        assert!(path.iter().all(|state| state.piece != Some(locked)));
+    }
+
+    #[test]
+    fn test_solve_with_centered_locked_piece() {
+        // Arrange: The locked piece is in the middle two columns, so boards are still folded left-to-right
+        let locked = Piece::new(5);
+        let rule = Rule::with_locked(
+            &Board::new(0x6278_0244_0553_11b3_11a9),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+            vec![locked],
+        )
+        .unwrap();
+        let mut unfolded = rule.clone();
+        unfolded.horizontal_symmetry = false;
+
+        // Act
+        let path = solve(&rule).unwrap();
+
+        // Assert
+        assert!(rule.horizontal_symmetry);
+        assert!(verify_solution(&rule, &path));
+        assert!(path.iter().all(|state| state.piece != Some(locked)));
+        assert_eq!(path.len(), 10);
+        assert_eq!(path.len(), solve(&unfolded).unwrap().len());
+    }
+
+    #[test]
+    fn test_solve_with_forbidden_board() {
+        // Arrange: Forbid a board on the only 7-move solutions
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0xff00_ff00_0000_0000_0000),
+        );
+        let shortest = solve(&rule).unwrap();
+        let forbidden_board = Board::new(0x2113_2113_4055_4086_97a6);
+        assert!(shortest.iter().any(|state| state.board == forbidden_board));
+        let mut forbidding = rule.clone();
+        forbidding
+            .forbidden
+            .insert(BoardKey::create(&rule, &forbidden_board).get_pattern());
+
+        // Act
+        let path = solve(&forbidding).unwrap();
+
+        // Assert
+        assert_eq!(shortest.len() - 1, 7);
+        assert_eq!(path.len() - 1, 8);
+        assert!(
+            path.iter()
+                .all(|state| !forbidding.is_forbidden(&state.board))
+        );
+        assert!(verify_solution(&forbidding, &path));
+        assert!(!verify_solution(&forbidding, &shortest));
+    }
+
+    #[test]
+    fn test_solve_with_neighbor_order() {
+        // Arrange: Test that the exploration order decides which shortest path is found
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let mut reordered = rule.clone();
+        reordered.pieces.reverse();
+        reordered.direction_order.reverse();
+
+        // Act
+        let path = solve(&rule).unwrap();
+        let reordered_path = solve(&reordered).unwrap();
+
+        // Assert
+        assert_eq!(path.len(), reordered_path.len());
+        assert_ne!(path, reordered_path);
+        assert!(verify_solution(&reordered, &reordered_path));
+    }
+
+    #[test]
+    fn test_solve_never_repeats_symmetric_boards() {
+        // Arrange: Puzzles with left-to-right symmetric goals, one with symmetry pairs
+        let goal_mask = BitPattern::new(0x0000_0000_0000_0ff0_0ff0);
+        let rules = [
+            Rule::new(&Board::new(0x2113_2113_4455_0000_0000), &goal_mask),
+            Rule::new(&Board::new(0x2113_2113_4556_4006_0000), &goal_mask),
+        ];
+        assert!(!rules[0].pairs.is_empty());
+        for rule in &rules {
+            // Act
+            let path = solve(rule).unwrap();
+
+            // Assert
+            let keys = path
+                .iter()
+                .map(|state| BoardKey::create(rule, &state.board))
+                .collect::<HashSet<_>>();
+            assert_eq!(keys.len(), path.len());
+        }
+    }
+
+    #[test]
+    fn test_get_neighbors_with_keys() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        for state in random_walk(&rule.start, 30, 5) {
+            // Act
+            let neighbors = get_neighbors_with_keys(&rule, &state);
+
+            // Assert
+            assert_eq!(
+                neighbors.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>(),
+                get_neighbors(&rule, &state)
+            );
+            for (next_state, key) in &neighbors {
+                assert_eq!(*key, BoardKey::create(&rule, &next_state.board));
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_neighbors_with_reversal() {
+        // Arrange
+        let mut rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let state = State::new(&rule.start);
+        let neighbors = get_neighbors(&rule, &state);
+        rule.allow_reversal = true;
+
+        // Act
+        let reversible_neighbors = get_neighbors(&rule, &state);
+
+        // Assert: Each of the four movable pieces may also go back where it was
+        let extra = reversible_neighbors
+            .iter()
+            .filter(|s| !neighbors.contains(s))
+            .collect::<Vec<_>>();
+        assert_eq!(reversible_neighbors.len(), neighbors.len() + 4);
+        assert_eq!(extra.len(), 4);
+        for s in extra {
+            assert_eq!(s.board, rule.start);
+            assert!(matches!(s.path, MovePath::Two(d1, d2) if d1.reversed() == d2));
+        }
+    }
+
+    #[test]
+    fn test_solve_with_tabu_window_keeps_history_apart() {
+        // Arrange: The shortest path reaches a board with other pieces in the tabu window than the first one
+        let mut rule = Rule::new(
+            &Board::new(0x0000_2113_2113_4455_0000),
+            &BitPattern::new(0x0000_0000_0000_ff00_ff00),
+        );
+        rule.tabu_window = 2;
+
+        // Act
+        let path = solve(&rule).unwrap();
+
+        // Assert
+        assert!(verify_solution(&rule, &path));
+        assert_eq!(path.len() - 1, 7);
+        assert_eq!(solve_weighted(&rule).unwrap().len(), path.len());
+    }
+
+    #[test]
+    fn test_solve_keeps_empties() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4006_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+
+        // Act
+        let path = solve(&rule).unwrap();
+
+        // Assert
+        for state in &path {
+            assert_eq!(state.empties, state.board.pattern.mask_of(Piece::new(0)));
+            assert_eq!(state.empty_cells(), state.board.empty_cells());
+        }
+    }
+
+    #[test]
+    fn test_get_neighbors_keeps_empties() {
+        // Arrange: Follow the neighbors of any piece for many moves, with and without wrapping around the edges
+        let mut rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        rule.tabu_window = 0;
+        for wrap in [false, true] {
+            rule.wrap = wrap;
+            let mut state = State::new(&rule.start);
+            for i in 0..100 {
+                // Act
+                let neighbors = get_neighbors(&rule, &state);
+                state = neighbors[i * 7 % neighbors.len()].clone();
+
+                // Assert
+                assert_eq!(state.empties, state.board.pattern.mask_of(Piece::new(0)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_neighbors_with_tabu_window() {
+        // Arrange: Test that get_neighbors does not move pieces within the tabu window
+        let mut rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        rule.tabu_window = 2;
+        let state = State {
+            board: rule.start.clone(),
+            piece: Some(Piece::new(8)),
+            path: MovePath::None,
+            earlier_pieces: RecentPieces::from_newest(&[Piece::new(7)]),
+            empties: rule.start.empty_mask(),
+        };
+
+        // Act
+        let neighbors = get_neighbors(&rule, &state);
+
+        // Assert
+        let moved_pieces = neighbors
+            .iter()
+            .map(|s| s.piece.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            moved_pieces,
+            vec![
+                Piece::new(9),
+                Piece::new(9),
+                Piece::new(0xa),
+                Piece::new(0xa)
+            ]
+        );
+        assert_eq!(
+            neighbors[0].earlier_pieces,
+            RecentPieces::from_newest(&[Piece::new(8)])
+        );
+        assert_eq!(
+            neighbors[0].tabu_pieces(rule.tabu_window),
+            RecentPieces::from_newest(&[Piece::new(9), Piece::new(8)])
+        );
+    }
+
     #[test]
     fn test_create_key() {
         // Arrange: Test BoardKey::create produces expected key
@@ -195,4 +1891,128 @@ mod tests {
         };
         assert_eq!(actual_key, expected_key);
     }
+
+    #[test]
+    fn test_create_key_with_vertical_symmetry() {
+        // Arrange: Test BoardKey::create collapses vertically flipped boards
+        let rule = Rule::with_goal(
+            &Board::new(0x2455_2400_6811_6311_739a),
+            GoalCondition::Avoids(BitPattern::new(0x0000_0000_ffff_0000_0000)),
+        );
+        let board = Board::new(0x2455_2400_6811_6311_739a);
+        let flipped_board = Board::new(0x739a_6311_6811_2400_2455);
+        // Act
+        let key = BoardKey::create(&rule, &board);
+        let flipped_key = BoardKey::create(&rule, &flipped_board);
+        // Assert
+        assert_eq!(key, flipped_key);
+    }
+
+    #[test]
+    fn test_solve_with_vertical_symmetry() {
+        // Arrange
+        let rule = Rule::with_goal(
+            &Board::new(0x2455_2400_6811_6311_739a),
+            GoalCondition::Avoids(BitPattern::new(0x0000_0000_ffff_0000_0000)),
+        );
+        let mut unfolded = rule.clone();
+        unfolded.vertical_symmetry = false;
+        // Act
+        let path = solve(&rule).unwrap();
+        // Assert
+        assert!(rule.vertical_symmetry);
+        assert!(verify_solution(&rule, &path));
+        assert_eq!(path.len(), 11);
+        assert_eq!(solve(&unfolded).unwrap().len(), 11);
+    }
+
+    /// Returns the shape class of each cell of the board, found from the shape and position of each piece,
+    /// as the smaller of the layout and its mirror image.
+    fn canonical_shape_layout(board: &Board) -> [[u8; 4]; 5] {
+        let mut layout = [[b'.'; 4]; 5];
+        for piece in board.pieces() {
+            let (row, col) =
+                super::super::rule::top_left_cell(&board.pattern.mask_of(piece)).unwrap();
+            let cells: &[(usize, usize, u8)] = match classify(board, piece) {
+                Shape::Small => &[(0, 0, b's')],
+                Shape::Horizontal => &[(0, 0, b'<'), (0, 1, b'>')],
+                Shape::Vertical => &[(0, 0, b'^'), (1, 0, b'v')],
+                Shape::Large => &[(0, 0, b'L'), (0, 1, b'L'), (1, 0, b'L'), (1, 1, b'L')],
+                shape => panic!("unexpected shape: {shape:?}"),
+            };
+            for &(dr, dc, class) in cells {
+                layout[row + dr][col + dc] = class;
+            }
+        }
+        let mut mirrored = layout;
+        for cells in &mut mirrored {
+            cells.reverse();
+            for class in cells.iter_mut() {
+                *class = match *class {
+                    b'<' => b'>',
+                    b'>' => b'<',
+                    class => class,
+                };
+            }
+        }
+        layout.min(mirrored)
+    }
+
+    #[test]
+    fn test_board_key_as_u64() {
+        // Arrange: The standard puzzle has several vertical and small pieces, which packing may merge
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let mut boards = HashMap::from([(
+            BoardKey::create(&rule, &rule.start).get_pattern(),
+            rule.start.clone(),
+        )]);
+        let mut stack = vec![rule.start.clone()];
+        while let Some(board) = stack.pop() {
+            for next_state in get_neighbors(&rule, &State::new(&board)) {
+                let key = BoardKey::create(&rule, &next_state.board).get_pattern();
+                if boards.len() < 5000 && !boards.contains_key(&key) {
+                    boards.insert(key, next_state.board.clone());
+                    stack.push(next_state.board);
+                }
+            }
+        }
+
+        // Act
+        let packed = boards
+            .iter()
+            .map(|(&key, board)| {
+                let packed = BoardKey { key }.as_u64().unwrap();
+                (packed, canonical_shape_layout(board))
+            })
+            .collect::<HashSet<_>>();
+
+        // Assert: Equal packed keys are the same layout of shapes, so packing never merges different boards
+        let packed_keys = packed.iter().map(|&(key, _)| key).collect::<HashSet<_>>();
+        assert_eq!(packed_keys.len(), packed.len());
+        // Swapping pieces of the same shape outside the symmetry pairs does merge keys.
+        // A layout may still have two packed keys, one mirrored, which only costs a revisit.
+        assert!(packed.len() < boards.len());
+    }
+
+    #[test]
+    fn test_board_key_as_u64_merges_same_shapes() {
+        // Arrange
+        let key = BoardKey {
+            key: BitPattern::new(0x2113_2113_4556_4786_900a),
+        };
+        let swapped = BoardKey {
+            key: BitPattern::new(0x2113_2113_4556_4876_900a),
+        };
+        let two_large = BoardKey {
+            key: BitPattern::new(0x1122_1122_3345_6789_a00b),
+        };
+
+        // Act & Assert
+        assert_eq!(key.as_u64(), Some(0o4664_5665_4234_5115_1001));
+        assert_eq!(key.as_u64(), swapped.as_u64());
+        assert_eq!(two_large.as_u64(), None);
+    }
 }
@@ -0,0 +1,12 @@
+use super::State;
+
+/// The outcome of a search for a solution that may be cut off before it completes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SolveOutcome {
+    /// A shortest path from the start to the goal.
+    Solved(Vec<State>),
+    /// The puzzle cannot be solved.
+    Unsolvable,
+    /// The search was cut off before it completed.
+    TimedOut,
+}
@@ -1,10 +1,501 @@
+use super::BitPattern;
 use super::Board;
+use super::Direction;
 use super::MovePath;
 use super::Piece;
+use super::Rule;
+use super::direction::ALL_DIRECTIONS;
+use std::collections::HashSet;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct State {
     pub board: Board,
     pub piece: Option<Piece>,
     pub path: MovePath,
+    /// The pieces moved before `piece`, newest first, as many as the tabu window of the rule keeps.
+    pub earlier_pieces: RecentPieces,
+    /// The mask of the empty cells of the board, as by `Board::empty_mask`.
+    ///
+    /// `get_neighbors` updates it from the cells of the moved piece instead of scanning the new board.
+    pub empties: BitPattern,
+}
+
+/// A short history of moved pieces, newest first, packed four bits per piece into a `u64`.
+///
+/// The pieces within a tabu window are all different, so the 16 slots hold any window of the 15 pieces.
+#[derive(Clone, Copy, Default, PartialOrd, Ord, PartialEq, Eq, Hash, Debug)]
+pub struct RecentPieces {
+    packed: u64,
+}
+
+impl RecentPieces {
+    /// Creates the history of the given pieces, newest first, keeping at most 16 of them.
+    pub fn from_newest(pieces: &[Piece]) -> Self {
+        pieces
+            .iter()
+            .rev()
+            .fold(Self::default(), |history, &piece| history.pushed(piece, 16))
+    }
+
+    /// Returns the history with the given piece moved last, keeping only the `len` newest pieces.
+    pub fn pushed(&self, piece: Piece, len: usize) -> Self {
+        let mask = match len {
+            0 => 0,
+            1..16 => (1 << (len * 4)) - 1,
+            _ => u64::MAX,
+        };
+        Self {
+            packed: ((self.packed << 4) | piece.id as u64) & mask,
+        }
+    }
+
+    /// Checks if the piece is in the history.
+    pub fn contains(&self, piece: Piece) -> bool {
+        piece.id != 0 && self.iter().any(|p| p == piece)
+    }
+
+    /// Returns the pieces of the history, newest first.
+    pub fn iter(&self) -> impl Iterator<Item = Piece> {
+        let packed = self.packed;
+        (0..16)
+            .map(move |i| ((packed >> (i * 4)) & 0xf) as u8)
+            .take_while(|&id| id != 0)
+            .map(Piece::new)
+    }
+
+    /// Returns the history with each piece replaced as given, such as by its partner of a symmetry pair.
+    pub fn map(&self, f: impl Fn(Piece) -> Piece) -> Self {
+        let packed = self.iter().enumerate().fold(0, |packed, (i, piece)| {
+            packed | (f(piece).id as u64) << (i * 4)
+        });
+        Self { packed }
+    }
+
+    /// Returns the history packed into a `u64`, the newest piece in the lowest four bits.
+    pub fn packed(&self) -> u64 {
+        self.packed
+    }
+}
+
+impl State {
+    /// Creates the initial `State` for the given board, before any piece has moved.
+    pub fn new(board: &Board) -> Self {
+        Self {
+            board: board.clone(),
+            piece: None,
+            path: MovePath::None,
+            earlier_pieces: RecentPieces::default(),
+            empties: board.empty_mask(),
+        }
+    }
+
+    /// Returns the (row, column) of each empty cell from the cached mask, like `Board::empty_cells`.
+    pub fn empty_cells(&self) -> Vec<(usize, usize)> {
+        let value = self.empties.get_u128();
+        (0..20)
+            .filter(|&i| (value >> ((19 - i) * 4)) & 0xf != 0)
+            .map(|i| (i / 4, i % 4))
+            .collect()
+    }
+
+    /// Returns the pieces that may not be moved next under the given tabu window, newest first.
+    ///
+    /// These are the last moved `piece` and the earlier pieces, as many as the window holds.
+    pub fn tabu_pieces(&self, tabu_window: usize) -> RecentPieces {
+        match (self.piece, tabu_window) {
+            (Some(piece), 1..) => self.earlier_pieces.pushed(piece, tabu_window),
+            _ => RecentPieces::default(),
+        }
+    }
+
+    /// Describes the move that led from the previous board to this state, with a heuristic reason for it.
+    ///
+    /// A move of the rule's goal piece is explained by how it changes `Rule::goal_distance`, and a move
+    /// of another piece by which cells it leaves empty, e.g. `moves piece #7 down to clear the goal region`.
+    pub fn explain(&self, prev: &Board, rule: &Rule) -> String {
+        let Some(piece) = self.piece else {
+            return "starts from the initial board".to_string();
+        };
+        let action = format!(
+            "moves piece #{piece} {}",
+            self.path.to_string().to_lowercase()
+        );
+
+        let goal_piece = rule.goal_piece();
+        if piece == goal_piece {
+            let (before, after) = (rule.goal_distance(prev), rule.goal_distance(&self.board));
+            let goal_piece_mask = self.board.pattern.mask_of(goal_piece);
+            return if rule.goal.is_satisfied_by(goal_piece_mask) {
+                format!("{action} into the goal")
+            } else if after < before {
+                format!("{action}, {after} cells from the goal")
+            } else if after > before {
+                format!("{action} away from the goal to get around other pieces")
+            } else {
+                format!("{action}, keeping its distance to the goal")
+            };
+        }
+
+        let freed = prev.pattern.mask_of(piece) & !self.board.pattern.mask_of(piece);
+        let goal_piece_mask = self.board.pattern.mask_of(goal_piece);
+        let next_to_goal_piece = ALL_DIRECTIONS
+            .iter()
+            .map(|&direction| goal_piece_mask.moved(direction))
+            .fold(BitPattern::new(0), |cells, moved| cells | moved)
+            & !goal_piece_mask;
+        if (freed & rule.goal.mask()).is_not_empty() {
+            format!("{action} to clear the goal region")
+        } else if (freed & next_to_goal_piece).is_not_empty() {
+            format!("{action} to free a cell next to piece #{goal_piece}")
+        } else {
+            format!("{action} to make room")
+        }
+    }
+}
+
+/// Counts the steps of the solution path that move the given piece, such as the large piece.
+pub fn count_goal_piece_moves(path: &[State], goal_piece: Piece) -> usize {
+    path.iter()
+        .filter(|state| state.piece == Some(goal_piece))
+        .count()
+}
+
+/// Counts the single-cell moves of the solution path by axis, returning `(horizontal, vertical)`.
+///
+/// A move of the same piece twice counts each of its directions, so `Down and Right` counts once for each axis.
+pub fn count_by_axis(path: &[State]) -> (usize, usize) {
+    path.iter().flat_map(|state| state.path.directions()).fold(
+        (0, 0),
+        |(horizontal, vertical), direction| match direction {
+            Direction::Left | Direction::Right => (horizontal + 1, vertical),
+            Direction::Up | Direction::Down => (horizontal, vertical + 1),
+        },
+    )
+}
+
+/// Returns the boards of the solution path in order, without the moves between them.
+pub fn solution_boards(path: &[State]) -> Vec<Board> {
+    path.iter().map(|state| state.board.clone()).collect()
+}
+
+/// Returns the moves that undo the solution path, leading from its last board back to its first.
+///
+/// The moves are in reverse order, each moving the same piece along the reversed path.
+pub fn invert_path(path: &[State]) -> Vec<(Piece, MovePath)> {
+    path.iter()
+        .rev()
+        .filter_map(|state| state.piece.map(|piece| (piece, state.path.reversed())))
+        .collect()
+}
+
+/// Formats the moves of the path with one line for each run of consecutive moves of the same piece.
+///
+/// Each line lists the directions of the run, e.g. `Move piece #7: Down, Down, Right (3 steps)`.
+/// Only the presentation changes. A path found by the solver, which does not move the same piece twice
+/// in a row, gets one line for each move.
+pub fn format_piece_runs(path: &[State]) -> Vec<String> {
+    let mut runs: Vec<(Piece, Vec<Direction>)> = vec![];
+    for state in path {
+        let Some(piece) = state.piece else {
+            continue;
+        };
+        match runs.last_mut() {
+            Some((last, directions)) if *last == piece => {
+                directions.extend(state.path.directions());
+            }
+            _ => runs.push((piece, state.path.directions())),
+        }
+    }
+    runs.iter()
+        .map(|(piece, directions)| {
+            let steps = directions.len();
+            let names = directions
+                .iter()
+                .map(|direction| direction.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let unit = if steps == 1 { "step" } else { "steps" };
+            format!("Move piece #{piece}: {names} ({steps} {unit})")
+        })
+        .collect()
+}
+
+/// Removes the solutions that are the mirror image of an earlier one, keeping the first of each.
+///
+/// Solutions are compared as sequences of boards with the pieces numbered in the order they appear
+/// on the starting board, so a mirrored solution matches even when the mirrored pieces have other IDs,
+/// such as the left and right pieces swapped to keep the starting board the same.
+pub fn dedupe_symmetric_solutions(solutions: Vec<Vec<State>>) -> Vec<Vec<State>> {
+    let mut seen = HashSet::new();
+    solutions
+        .into_iter()
+        .filter(|path| seen.insert(canonical_grids(path)))
+        .collect()
+}
+
+/// Returns the grids of the path or of its mirror image, whichever is smaller after renumbering the pieces.
+fn canonical_grids(path: &[State]) -> Vec<[[u8; 4]; 5]> {
+    let grids = path
+        .iter()
+        .map(|state| state.board.to_grid())
+        .collect::<Vec<_>>();
+    let mirrored = grids
+        .iter()
+        .map(|grid| {
+            grid.map(|mut row| {
+                row.reverse();
+                row
+            })
+        })
+        .collect();
+    renumbered(grids).min(renumbered(mirrored))
+}
+
+/// Renumbers the pieces of the grids from 1 in the order they appear on the first grid, keeping `0` for empty spaces.
+fn renumbered(grids: Vec<[[u8; 4]; 5]>) -> Vec<[[u8; 4]; 5]> {
+    let mut ids = [0; 16];
+    let mut next = 1;
+    for &id in grids.first().into_iter().flatten().flatten() {
+        if id != 0 && ids[id as usize] == 0 {
+            ids[id as usize] = next;
+            next += 1;
+        }
+    }
+    grids
+        .into_iter()
+        .map(|grid| grid.map(|row| row.map(|id| ids[id as usize])))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+
+    #[test]
+    fn test_recent_pieces() {
+        // Arrange
+        let history = RecentPieces::from_newest(&[Piece::new(3), Piece::new(0xa)]);
+
+        // Act
+        let pushed = history.pushed(Piece::new(7), 2);
+
+        // Assert
+        assert_eq!(
+            pushed.iter().collect::<Vec<_>>(),
+            [Piece::new(7), Piece::new(3)]
+        );
+        assert!(pushed.contains(Piece::new(3)));
+        assert!(!pushed.contains(Piece::new(0xa)));
+        assert!(!pushed.contains(Piece::new(0)));
+        assert_eq!(history.pushed(Piece::new(7), 0), RecentPieces::default());
+    }
+
+    #[test]
+    fn test_tabu_pieces() {
+        // Arrange
+        let state = State {
+            earlier_pieces: RecentPieces::from_newest(&[Piece::new(3), Piece::new(0xa)]),
+            piece: Some(Piece::new(7)),
+            ..State::new(&Board::new(0x2113_2113_4556_4786_900a))
+        };
+
+        // Act & Assert
+        assert_eq!(state.tabu_pieces(0), RecentPieces::default());
+        assert_eq!(
+            state.tabu_pieces(1),
+            RecentPieces::from_newest(&[Piece::new(7)])
+        );
+        assert_eq!(
+            state.tabu_pieces(3),
+            RecentPieces::from_newest(&[Piece::new(7), Piece::new(3), Piece::new(0xa)])
+        );
+        assert_eq!(
+            State::new(&Board::new(0x2113_2113_4556_4786_900a)).tabu_pieces(2),
+            RecentPieces::default()
+        );
+    }
+
+    #[test]
+    fn test_count_goal_piece_moves() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let path = solve(&rule).unwrap();
+        // Act & Assert
+        assert_eq!(count_goal_piece_moves(&path, Piece::new(1)), 9);
+        assert_eq!(count_goal_piece_moves(&path[..1], Piece::new(1)), 0);
+    }
+
+    #[test]
+    fn test_count_by_axis() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let path = solve(&rule).unwrap();
+
+        // Act
+        let (horizontal, vertical) = count_by_axis(&path);
+
+        // Assert
+        assert_eq!((horizontal, vertical), (58, 60));
+        assert_eq!(
+            horizontal + vertical,
+            path.iter().map(|s| s.path.directions().len()).sum()
+        );
+        assert_eq!(count_by_axis(&path[..1]), (0, 0));
+    }
+
+    #[test]
+    fn test_solution_boards() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4455_0000_0000),
+            &BitPattern::new(0x0000_0000_0000_0ff0_0ff0),
+        );
+        let path = solve(&rule).unwrap();
+
+        // Act
+        let boards = solution_boards(&path);
+
+        // Assert
+        assert_eq!(boards.len(), path.len());
+        assert_eq!(boards.first(), Some(&rule.start));
+        assert!(rule.is_finished(boards.last().unwrap()));
+    }
+
+    #[test]
+    fn test_explain() {
+        // Arrange
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let rule = Rule::new(&start, &BitPattern::new(0x0000_0000_0000_0ff0_0ff0));
+        let step = |image: u128, id: u8, path: MovePath| State {
+            board: Board::new(image),
+            piece: Some(Piece::new(id)),
+            path,
+            earlier_pieces: RecentPieces::default(),
+            empties: Board::new(image).empty_mask(),
+        };
+
+        // Act & Assert
+        assert_eq!(
+            State::new(&start).explain(&start, &rule),
+            "starts from the initial board"
+        );
+        assert_eq!(
+            step(
+                0x2113_2113_4556_4086_970a,
+                7,
+                MovePath::One(Direction::Down)
+            )
+            .explain(&start, &rule),
+            "moves piece #7 down to clear the goal region"
+        );
+        assert_eq!(
+            step(
+                0x2113_2113_4556_4786_090a,
+                9,
+                MovePath::One(Direction::Right)
+            )
+            .explain(&start, &rule),
+            "moves piece #9 right to make room"
+        );
+        let before = Board::new(0x2003_2113_4116_4556_7789);
+        assert_eq!(
+            step(0x2113_2113_4006_4556_7789, 1, MovePath::One(Direction::Up))
+                .explain(&before, &rule),
+            "moves piece #1 up away from the goal to get around other pieces"
+        );
+    }
+
+    #[test]
+    fn test_invert_path() {
+        // Arrange
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0xff00_ff00_0000_0000_0000),
+        );
+        let path = solve(&rule).unwrap();
+
+        // Act
+        let moves = invert_path(&path);
+
+        // Assert
+        assert_eq!(moves.len(), path.len() - 1);
+        let goal_board = path.last().unwrap().board.clone();
+        let board = moves
+            .iter()
+            .try_fold(goal_board, |board, (piece, move_path)| {
+                board.apply(*piece, move_path)
+            });
+        assert_eq!(board, Some(rule.start));
+    }
+
+    #[test]
+    fn test_format_piece_runs() {
+        // Arrange: Piece #9 moves right twice in a row, and left again after piece #4 moves
+        let start = Board::new(0x2113_2113_4556_4786_900a);
+        let moves = [
+            (Piece::new(9), Direction::Right),
+            (Piece::new(9), Direction::Right),
+            (Piece::new(4), Direction::Down),
+            (Piece::new(9), Direction::Left),
+        ];
+        let boards = Board::replay_single_moves(&start, moves).unwrap();
+        let mut path = vec![State::new(&start)];
+        for ((piece, direction), board) in moves.into_iter().zip(&boards[1..]) {
+            path.push(State {
+                board: board.clone(),
+                piece: Some(piece),
+                path: MovePath::One(direction),
+                earlier_pieces: RecentPieces::default(),
+                empties: board.empty_mask(),
+            });
+        }
+
+        // Act
+        let lines = format_piece_runs(&path);
+
+        // Assert
+        assert_eq!(
+            lines,
+            [
+                "Move piece #9: Right, Right (2 steps)",
+                "Move piece #4: Down (1 step)",
+                "Move piece #9: Left (1 step)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dedupe_symmetric_solutions() {
+        // Arrange: The mirror image of a solution, with the symmetric pieces swapped back
+        let rule = Rule::new(
+            &Board::new(0x2113_2113_4556_4786_900a),
+            &BitPattern::new(0xff00_ff00_0000_0000_0000),
+        );
+        let path = solve(&rule).unwrap();
+        let pairs = symmetry_pairs(&rule.start, &BitPattern::new(0x0000_0000_0000_0ff0_0ff0));
+        let mirrored = path
+            .iter()
+            .map(|state| {
+                let pattern = state.board.pattern.mirrored().symmetrized(&pairs);
+                State::new(&Board::new(pattern.get_u128()))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(mirrored[0].board, rule.start);
+        assert_ne!(mirrored, path);
+        let shorter = path[..3].to_vec();
+
+        // Act
+        let deduped =
+            dedupe_symmetric_solutions(vec![path.clone(), mirrored, shorter.clone(), path.clone()]);
+
+        // Assert
+        assert_eq!(deduped, vec![path, shorter]);
+    }
 }
@@ -1,22 +1,43 @@
 use std::collections::HashSet;
+use std::hash::BuildHasherDefault;
 use std::hash::Hash;
+use std::hash::Hasher;
 
+/// A hash set using `KeyHasher`, which is much faster than the default SipHash for board keys.
+type KeySet<T> = HashSet<T, BuildHasherDefault<KeyHasher>>;
+
+/// Keeps the nodes visited in the last three generations of a breadth-first search, or in all of them.
+///
+/// The nodes are usually the raw `u128` of the canonical board key,
+/// which is smaller and faster to hash than the key struct itself.
 #[derive(Default)]
 pub struct VisitedHistory<T: Eq + Hash> {
-    current: HashSet<T>,
-    previous: HashSet<T>,
-    pre_previous: HashSet<T>,
+    current: KeySet<T>,
+    previous: KeySet<T>,
+    pre_previous: KeySet<T>,
     depth: usize,
+    /// Whether the older generations are merged into `pre_previous` instead of being forgotten.
+    keep_all: bool,
 }
 
 impl<T: Eq + Hash> VisitedHistory<T> {
     /// Creates a new `VisitedHistory`.
     pub fn new() -> Self {
         Self {
-            current: HashSet::new(),
-            previous: HashSet::new(),
-            pre_previous: HashSet::new(),
+            current: KeySet::default(),
+            previous: KeySet::default(),
+            pre_previous: KeySet::default(),
             depth: 0,
+            keep_all: false,
+        }
+    }
+
+    /// Creates a `VisitedHistory` that never forgets a node, for searches that may reach a node again
+    /// more than two generations after visiting it.
+    pub fn keeping_all() -> Self {
+        Self {
+            keep_all: true,
+            ..Self::new()
         }
     }
 
@@ -58,8 +79,130 @@ impl<T: Eq + Hash> VisitedHistory<T> {
             || self.pre_previous.contains(node)
     }
 
+    /// Forgets all visited nodes, keeping the allocated capacity for the next search.
+    pub fn clear(&mut self) {
+        self.current.clear();
+        self.previous.clear();
+        self.pre_previous.clear();
+        self.depth = 0;
+    }
+
     fn advance_generation(&mut self) {
-        self.pre_previous = std::mem::take(&mut self.previous);
-        self.previous = std::mem::take(&mut self.current);
+        if self.keep_all {
+            // Merge the previous generation into the older ones instead of forgetting the oldest.
+            self.pre_previous.extend(self.previous.drain());
+            std::mem::swap(&mut self.previous, &mut self.current);
+            return;
+        }
+        // Reuse the oldest generation's allocation for the new one.
+        std::mem::swap(&mut self.pre_previous, &mut self.previous);
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+}
+
+/// A fast non-cryptographic hasher for board keys, which are not chosen by an attacker.
+///
+/// The 80-bit board image is folded into 64 bits and multiplied by an odd constant,
+/// then the high bits are mixed into the low bits used to pick the bucket.
+#[derive(Default)]
+pub struct KeyHasher {
+    hash: u64,
+}
+
+/// An odd constant with well-distributed bits, taken from FxHash.
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        self.hash ^ (self.hash >> 32)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u64(byte as u64);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ n).wrapping_mul(SEED);
+    }
+
+    fn write_u128(&mut self, n: u128) {
+        self.write_u64(n as u64 ^ ((n >> 64) as u64).rotate_left(32));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_visit_keeps_three_generations() {
+        // Arrange
+        let mut history = VisitedHistory::new();
+
+        // Act & Assert
+        assert!(history.try_visit(1u128, 0));
+        assert!(!history.try_visit(1u128, 0));
+        assert!(history.try_visit(2u128, 1));
+        assert!(history.try_visit(3u128, 2));
+        assert!(!history.try_visit(1u128, 2));
+        assert!(history.try_visit(4u128, 3));
+        // Forgotten after three generations.
+        assert!(history.try_visit(1u128, 3));
+    }
+
+    #[test]
+    fn test_keeping_all_remembers_every_generation() {
+        // Arrange
+        let mut history = VisitedHistory::keeping_all();
+
+        // Act & Assert
+        assert!(history.try_visit(1u128, 0));
+        assert!(history.try_visit(2u128, 1));
+        assert!(history.try_visit(3u128, 2));
+        assert!(history.try_visit(4u128, 3));
+        assert!(history.try_visit(5u128, 4));
+        assert!(!history.try_visit(1u128, 4));
+        assert!(!history.try_visit(2u128, 5));
+    }
+
+    #[test]
+    fn test_clear_forgets_visited_nodes() {
+        // Arrange
+        let mut history = VisitedHistory::new();
+        history.try_visit(1u128, 0);
+        history.try_visit(2u128, 1);
+
+        // Act
+        history.clear();
+
+        // Assert
+        assert!(history.try_visit(1u128, 0));
+        assert!(history.try_visit(2u128, 1));
+    }
+
+    #[test]
+    fn test_raw_keys_dedup_like_patterns() {
+        // Arrange
+        let patterns = [
+            0x2113_2113_4556_4786_900a,
+            0x2113_2113_4556_4086_970a,
+            0x2113_2113_4556_4786_900a,
+            0x3112_3112_6554_6874_a009,
+            0x2113_2113_4556_4086_970a,
+        ]
+        .map(super::super::BitPattern::new);
+        let mut pattern_history = VisitedHistory::new();
+        let mut raw_history = VisitedHistory::new();
+
+        // Act
+        let pattern_results = patterns.map(|p| pattern_history.try_visit(p, 0));
+        let raw_results = patterns.map(|p| raw_history.try_visit(p.get_u128(), 0));
+
+        // Assert
+        assert_eq!(pattern_results, [true, true, false, true, false]);
+        assert_eq!(raw_results, pattern_results);
     }
 }
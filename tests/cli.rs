@@ -0,0 +1,360 @@
+use std::process::Command;
+
+/// Runs the klotski binary with the arguments, returning its exit status and standard output.
+fn run_klotski(args: &[&str]) -> (Option<i32>, String) {
+    let (code, stdout, _stderr) = run_klotski_with_stderr(args);
+    (code, stdout)
+}
+
+/// Runs the klotski binary with the arguments, returning its exit status, standard output and standard error.
+fn run_klotski_with_stderr(args: &[&str]) -> (Option<i32>, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_klotski"))
+        .args(args)
+        .output()
+        .expect("failed to run klotski");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    (output.status.code(), stdout, stderr)
+}
+
+#[test]
+fn test_check_invalid_board() {
+    // Act
+    let (code, stdout, stderr) =
+        run_klotski_with_stderr(&["--check", "0x2113_2113_4556_4786_9aaa"]);
+
+    // Assert
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout, "");
+    assert_eq!(
+        stderr,
+        "Error: START_IMAGE must have only two empty spaces.\n"
+    );
+}
+
+#[test]
+fn test_quiet_solvable() {
+    // Act
+    let (code, stdout) = run_klotski(&[
+        "--quiet",
+        "0x2113_2113_4556_4786_900a",
+        "0xff00_ff00_0000_0000_0000",
+    ]);
+
+    // Assert
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn test_quiet_unsolvable() {
+    // Act
+    let (code, stdout) = run_klotski(&[
+        "--quiet",
+        "0x2113_2113_4455_6677_8009",
+        "0x0000_0000_0000_0ff0_0ff0",
+    ]);
+
+    // Assert
+    assert_eq!(code, Some(2));
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn test_quiet_scramble() {
+    // Act
+    let (code, stdout) = run_klotski(&[
+        "--quiet",
+        "--scramble",
+        "20",
+        "0x2113_2113_4556_4786_900a",
+        "0xff00_ff00_0000_0000_0000",
+    ]);
+
+    // Assert
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn test_quiet_invalid() {
+    // Act
+    let (code, stdout) = run_klotski(&["--quiet", "0x2113_2113_4556_4786_9aaa"]);
+
+    // Assert
+    assert_eq!(code, Some(1));
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn test_quiet_check() {
+    // Act
+    let valid = run_klotski(&["--quiet", "--check", "0x2113_2113_4556_4786_900a"]);
+    let invalid = run_klotski(&["--quiet", "--check", "0x2113_2113_4556_4786_9aaa"]);
+
+    // Assert
+    assert_eq!(valid, (Some(0), String::new()));
+    assert_eq!(invalid, (Some(1), String::new()));
+}
+
+#[test]
+fn test_quiet_batch() {
+    // Arrange
+    let dir = std::env::temp_dir();
+    let solvable = dir.join(format!(
+        "klotski-quiet-batch-{}-solvable.txt",
+        std::process::id()
+    ));
+    let unsolvable = dir.join(format!(
+        "klotski-quiet-batch-{}-unsolvable.txt",
+        std::process::id()
+    ));
+    std::fs::write(&solvable, SHORT_PUZZLE.join(" ")).unwrap();
+    std::fs::write(
+        &unsolvable,
+        format!(
+            "{}\n0x2113_2113_4455_6677_8009 0x0000_0000_0000_0ff0_0ff0\n",
+            SHORT_PUZZLE.join(" ")
+        ),
+    )
+    .unwrap();
+
+    // Act
+    let all_solved = run_klotski(&["--quiet", "--batch", solvable.to_str().unwrap()]);
+    let not_all_solved = run_klotski(&["--quiet", "--batch", unsolvable.to_str().unwrap()]);
+    std::fs::remove_file(&solvable).unwrap();
+    std::fs::remove_file(&unsolvable).unwrap();
+
+    // Assert
+    assert_eq!(all_solved, (Some(0), String::new()));
+    assert_eq!(not_all_solved, (Some(2), String::new()));
+}
+
+/// The arguments of a short puzzle, moving the large piece to the top left in 7 moves.
+const SHORT_PUZZLE: [&str; 2] = ["0x2113_2113_4556_4786_900a", "0xff00_ff00_0000_0000_0000"];
+
+/// Runs the klotski binary on the short puzzle with the output format, returning its standard output.
+fn run_format(format: &str) -> String {
+    let (code, stdout) = run_klotski(&["--format", format, SHORT_PUZZLE[0], SHORT_PUZZLE[1]]);
+    assert_eq!(code, Some(0));
+    stdout
+}
+
+#[test]
+fn test_format_text() {
+    // Act
+    let stdout = run_format("text");
+
+    // Assert
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 7);
+    for (i, line) in lines.iter().enumerate() {
+        assert!(line.starts_with(&format!("step {}: Move piece #", i + 1)));
+    }
+    assert_eq!(stdout, run_klotski(&SHORT_PUZZLE).1);
+}
+
+#[test]
+fn test_format_json() {
+    // Act
+    let stdout = run_format("json");
+
+    // Assert
+    let Some(Json::Object(document)) = parse_json(&stdout) else {
+        panic!("not a JSON object: {stdout}");
+    };
+    let [(key, Json::Array(steps))] = &document[..] else {
+        panic!("unexpected members: {document:?}");
+    };
+    assert_eq!(key, "steps");
+    assert_eq!(steps.len(), 7);
+    let Json::Object(last_step) = &steps[6] else {
+        panic!("not a step object: {:?}", steps[6]);
+    };
+    assert_eq!(
+        last_step,
+        &[
+            ("piece".to_string(), Json::String("1".to_string())),
+            ("path".to_string(), Json::String("Left".to_string())),
+            (
+                "board".to_string(),
+                Json::String("0x1103_1103_2455_2486_97a6".to_string())
+            ),
+        ]
+    );
+}
+
+/// A JSON value, as far as the output of `--format json` needs.
+#[derive(Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// Parses a whole JSON document, allowing whitespace around it, or returns `None` if it is not valid JSON.
+fn parse_json(text: &str) -> Option<Json> {
+    let mut chars = text.chars().peekable();
+    let value = parse_json_value(&mut chars)?;
+    skip_json_whitespace(&mut chars);
+    chars.next().is_none().then_some(value)
+}
+
+type JsonChars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+fn skip_json_whitespace(chars: &mut JsonChars) {
+    while chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+}
+
+fn parse_json_value(chars: &mut JsonChars) -> Option<Json> {
+    skip_json_whitespace(chars);
+    match *chars.peek()? {
+        '{' => {
+            chars.next();
+            let mut members = vec![];
+            skip_json_whitespace(chars);
+            if chars.next_if_eq(&'}').is_some() {
+                return Some(Json::Object(members));
+            }
+            loop {
+                skip_json_whitespace(chars);
+                let Json::String(key) = parse_json_value(chars)? else {
+                    return None;
+                };
+                skip_json_whitespace(chars);
+                chars.next_if_eq(&':')?;
+                members.push((key, parse_json_value(chars)?));
+                skip_json_whitespace(chars);
+                match chars.next()? {
+                    ',' => continue,
+                    '}' => return Some(Json::Object(members)),
+                    _ => return None,
+                }
+            }
+        }
+        '[' => {
+            chars.next();
+            let mut elements = vec![];
+            skip_json_whitespace(chars);
+            if chars.next_if_eq(&']').is_some() {
+                return Some(Json::Array(elements));
+            }
+            loop {
+                elements.push(parse_json_value(chars)?);
+                skip_json_whitespace(chars);
+                match chars.next()? {
+                    ',' => continue,
+                    ']' => return Some(Json::Array(elements)),
+                    _ => return None,
+                }
+            }
+        }
+        '"' => {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next()? {
+                    '"' => return Some(Json::String(string)),
+                    '\\' => match chars.next()? {
+                        'n' => string.push('\n'),
+                        't' => string.push('\t'),
+                        'r' => string.push('\r'),
+                        'b' => string.push('\u{8}'),
+                        'f' => string.push('\u{c}'),
+                        'u' => {
+                            let hex = (0..4).map(|_| chars.next()).collect::<Option<String>>()?;
+                            string.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                        }
+                        c @ ('"' | '\\' | '/') => string.push(c),
+                        _ => return None,
+                    },
+                    c if c < ' ' => return None,
+                    c => string.push(c),
+                }
+            }
+        }
+        't' | 'f' | 'n' => {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                word.push(c);
+            }
+            match word.as_str() {
+                "true" => Some(Json::Bool(true)),
+                "false" => Some(Json::Bool(false)),
+                "null" => Some(Json::Null),
+                _ => None,
+            }
+        }
+        _ => {
+            let mut number = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+                number.push(c);
+            }
+            number.parse().ok().map(Json::Number)
+        }
+    }
+}
+
+#[test]
+fn test_parse_json_rejects_trailing_text() {
+    // Act & Assert
+    assert_eq!(
+        parse_json(r#"{"a":[1,"x\"y",true,null]}"#),
+        Some(Json::Object(vec![(
+            "a".to_string(),
+            Json::Array(vec![
+                Json::Number(1.0),
+                Json::String("x\"y".to_string()),
+                Json::Bool(true),
+                Json::Null,
+            ])
+        )]))
+    );
+    assert_eq!(parse_json("{}\npiece #1 moves: 1"), None);
+    assert_eq!(parse_json(r#"{"a":}"#), None);
+}
+
+#[test]
+fn test_format_notation() {
+    // Act
+    let stdout = run_format("notation");
+
+    // Assert: The moves reach the goal when played back
+    let mut board = klotski::Board::new(0x2113_2113_4556_4786_900a);
+    for line in stdout.lines() {
+        let mut words = line.split_whitespace();
+        let piece = klotski::Piece::new(u8::from_str_radix(words.next().unwrap(), 16).unwrap());
+        for word in words {
+            let direction = match word {
+                "U" => klotski::Direction::Up,
+                "D" => klotski::Direction::Down,
+                "L" => klotski::Direction::Left,
+                "R" => klotski::Direction::Right,
+                _ => panic!("unexpected direction: {word}"),
+            };
+            board = board.move_piece(piece, direction).unwrap();
+        }
+    }
+    assert_eq!(stdout.lines().count(), 7);
+    assert_eq!(board, klotski::Board::new(0x1103_1103_2455_2486_97a6));
+}
+
+#[test]
+fn test_format_grid() {
+    // Act
+    let stdout = run_format("grid");
+
+    // Assert
+    let grids = stdout.split_terminator("\n\n").collect::<Vec<_>>();
+    assert_eq!(grids.len(), 8);
+    for grid in &grids {
+        let rows = grid.lines().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 5);
+        assert!(rows.iter().all(|row| row.len() == 4));
+    }
+    assert_eq!(grids[0], "2113\n2113\n4556\n4786\n9..a");
+    assert_eq!(grids[7], "11.3\n11.3\n2455\n2486\n97a6");
+}
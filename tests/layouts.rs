@@ -0,0 +1,20 @@
+use klotski::layouts;
+
+#[test]
+fn test_all_layouts() {
+    for layout in layouts::all() {
+        // Arrange
+        let rule = layout.rule();
+        // Act
+        let path = klotski::solve(&rule);
+        // Assert
+        match layout.moves {
+            Some(moves) => {
+                let path = path.unwrap_or_else(|| panic!("{} is not solved", layout.name));
+                assert!(klotski::verify_solution(&rule, &path), "{}", layout.name);
+                assert_eq!(path.len() - 1, moves, "{}", layout.name);
+            }
+            None => assert!(path.is_none(), "{} is solved", layout.name),
+        }
+    }
+}